@@ -0,0 +1,205 @@
+use libftd2xx::{
+    BitsPerWord, DeviceInfo, DeviceStatus, FtStatus, Ftdi, FtdiCommon, Parity, StopBits,
+    TimeoutError, Version,
+};
+use std::time::Duration;
+
+/// The operations an open dmx device needs from the underlying hardware. The methods mirror the
+/// corresponding libftd2xx calls, so the ftdi implementation simply forwards them.
+///
+/// A backend is expected to be opened already. Use OpenDMX::new_with_backend() to create a
+/// device for a custom backend.
+pub trait DmxBackend: Send {
+    fn device_info(&mut self) -> Result<DeviceInfo, FtStatus>;
+    /// Version of the device driver. Fails with FtStatus::NOT_SUPPORTED by default.
+    fn driver_version(&mut self) -> Result<Version, FtStatus> {
+        Err(FtStatus::NOT_SUPPORTED)
+    }
+    fn reset(&mut self) -> Result<(), FtStatus>;
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), FtStatus>;
+    fn set_data_characteristics(
+        &mut self,
+        bits_per_word: BitsPerWord,
+        stop_bits: StopBits,
+        parity: Parity,
+    ) -> Result<(), FtStatus>;
+    fn set_timeouts(
+        &mut self,
+        read_time_out: Duration,
+        write_time_out: Duration,
+    ) -> Result<(), FtStatus>;
+    fn set_latency_timer(&mut self, timer: Duration) -> Result<(), FtStatus>;
+    fn latency_timer(&mut self) -> Result<Duration, FtStatus>;
+    fn set_usb_parameters(&mut self, transfer_size: u32) -> Result<(), FtStatus>;
+    fn set_flow_control_none(&mut self) -> Result<(), FtStatus>;
+    fn set_flow_control_rts_cts(&mut self) -> Result<(), FtStatus>;
+    fn set_flow_control_dtr_dsr(&mut self) -> Result<(), FtStatus>;
+    fn set_flow_control_xon_xoff(&mut self, xon: u8, xoff: u8) -> Result<(), FtStatus>;
+    fn set_rts(&mut self) -> Result<(), FtStatus>;
+    fn clear_rts(&mut self) -> Result<(), FtStatus>;
+    fn purge_rx(&mut self) -> Result<(), FtStatus>;
+    fn purge_tx(&mut self) -> Result<(), FtStatus>;
+    /// Set the break condition on (true) or off (false).
+    fn set_break(&mut self, on: bool) -> Result<(), FtStatus>;
+    fn queue_status(&mut self) -> Result<usize, FtStatus>;
+    fn read_all(&mut self, buf: &mut [u8]) -> Result<(), TimeoutError>;
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), TimeoutError>;
+    /// Write as many bytes as possible before the time out elapses. Returns the number of bytes
+    /// written.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, FtStatus>;
+    fn status(&mut self) -> Result<DeviceStatus, FtStatus>;
+    fn close(&mut self) -> Result<(), FtStatus>;
+
+    /// The underlying ftdi device, if the backend is one (see OpenDMX::ftdi_mut()).
+    fn as_ftdi_mut(&mut self) -> Option<&mut Ftdi> {
+        None
+    }
+
+    /// Size of the EEPROM user area in bytes. Fails with FtStatus::NOT_SUPPORTED by default.
+    #[cfg(feature = "eeprom")]
+    fn eeprom_user_size(&mut self) -> Result<usize, FtStatus> {
+        Err(FtStatus::NOT_SUPPORTED)
+    }
+
+    /// Read the start of the EEPROM user area. Returns the number of bytes read.
+    #[cfg(feature = "eeprom")]
+    fn eeprom_user_read(&mut self, _buf: &mut [u8]) -> Result<usize, FtStatus> {
+        Err(FtStatus::NOT_SUPPORTED)
+    }
+
+    /// Write the start of the EEPROM user area.
+    #[cfg(feature = "eeprom")]
+    fn eeprom_user_write(&mut self, _buf: &[u8]) -> Result<(), FtStatus> {
+        Err(FtStatus::NOT_SUPPORTED)
+    }
+}
+
+impl DmxBackend for Ftdi {
+    fn device_info(&mut self) -> Result<DeviceInfo, FtStatus> {
+        FtdiCommon::device_info(self)
+    }
+
+    fn driver_version(&mut self) -> Result<Version, FtStatus> {
+        FtdiCommon::driver_version(self)
+    }
+
+    fn reset(&mut self) -> Result<(), FtStatus> {
+        FtdiCommon::reset(self)
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), FtStatus> {
+        FtdiCommon::set_baud_rate(self, baud_rate)
+    }
+
+    fn set_data_characteristics(
+        &mut self,
+        bits_per_word: BitsPerWord,
+        stop_bits: StopBits,
+        parity: Parity,
+    ) -> Result<(), FtStatus> {
+        FtdiCommon::set_data_characteristics(self, bits_per_word, stop_bits, parity)
+    }
+
+    fn set_timeouts(
+        &mut self,
+        read_time_out: Duration,
+        write_time_out: Duration,
+    ) -> Result<(), FtStatus> {
+        FtdiCommon::set_timeouts(self, read_time_out, write_time_out)
+    }
+
+    fn set_latency_timer(&mut self, timer: Duration) -> Result<(), FtStatus> {
+        FtdiCommon::set_latency_timer(self, timer)
+    }
+
+    fn latency_timer(&mut self) -> Result<Duration, FtStatus> {
+        FtdiCommon::latency_timer(self)
+    }
+
+    fn set_usb_parameters(&mut self, transfer_size: u32) -> Result<(), FtStatus> {
+        FtdiCommon::set_usb_parameters(self, transfer_size)
+    }
+
+    fn set_flow_control_none(&mut self) -> Result<(), FtStatus> {
+        FtdiCommon::set_flow_control_none(self)
+    }
+
+    fn set_flow_control_rts_cts(&mut self) -> Result<(), FtStatus> {
+        FtdiCommon::set_flow_control_rts_cts(self)
+    }
+
+    fn set_flow_control_dtr_dsr(&mut self) -> Result<(), FtStatus> {
+        FtdiCommon::set_flow_control_dtr_dsr(self)
+    }
+
+    fn set_flow_control_xon_xoff(&mut self, xon: u8, xoff: u8) -> Result<(), FtStatus> {
+        FtdiCommon::set_flow_control_xon_xoff(self, xon, xoff)
+    }
+
+    fn set_rts(&mut self) -> Result<(), FtStatus> {
+        FtdiCommon::set_rts(self)
+    }
+
+    fn clear_rts(&mut self) -> Result<(), FtStatus> {
+        FtdiCommon::clear_rts(self)
+    }
+
+    fn purge_rx(&mut self) -> Result<(), FtStatus> {
+        FtdiCommon::purge_rx(self)
+    }
+
+    fn purge_tx(&mut self) -> Result<(), FtStatus> {
+        FtdiCommon::purge_tx(self)
+    }
+
+    fn set_break(&mut self, on: bool) -> Result<(), FtStatus> {
+        if on {
+            FtdiCommon::set_break_on(self)
+        } else {
+            FtdiCommon::set_break_off(self)
+        }
+    }
+
+    fn queue_status(&mut self) -> Result<usize, FtStatus> {
+        FtdiCommon::queue_status(self)
+    }
+
+    fn read_all(&mut self, buf: &mut [u8]) -> Result<(), TimeoutError> {
+        FtdiCommon::read_all(self, buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), TimeoutError> {
+        FtdiCommon::write_all(self, buf)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, FtStatus> {
+        FtdiCommon::write(self, buf)
+    }
+
+    fn status(&mut self) -> Result<DeviceStatus, FtStatus> {
+        FtdiCommon::status(self)
+    }
+
+    fn close(&mut self) -> Result<(), FtStatus> {
+        FtdiCommon::close(self)
+    }
+
+    fn as_ftdi_mut(&mut self) -> Option<&mut Ftdi> {
+        Some(self)
+    }
+
+    #[cfg(feature = "eeprom")]
+    fn eeprom_user_size(&mut self) -> Result<usize, FtStatus> {
+        FtdiCommon::eeprom_user_size(self)
+    }
+
+    #[cfg(feature = "eeprom")]
+    fn eeprom_user_read(&mut self, buf: &mut [u8]) -> Result<usize, FtStatus> {
+        FtdiCommon::eeprom_user_read(self, buf)
+    }
+
+    #[cfg(feature = "eeprom")]
+    fn eeprom_user_write(&mut self, buf: &[u8]) -> Result<(), FtStatus> {
+        FtdiCommon::eeprom_user_write(self, buf)
+    }
+}
@@ -0,0 +1,445 @@
+use crate::layer::Layers;
+#[cfg(feature = "rdm")]
+use crate::RdmTurnaround;
+use crate::{
+    ChannelProfile, ConnectionState, DeviceProfile, DmxBackend, DmxError, FrameSize,
+    IntensityCurve, OnControlLoss, OpenDMX, OpenDmxProtocol, RefreshMode, ResetMode,
+    SharedSnapshot, Timing, UpdateFrequency, DMX_BREAK, DMX_MAB, DMX_MAX_CHANNELS,
+    DMX_UNIVERSE_SIZE,
+};
+use libftd2xx::{FtStatus, Ftdi, StopBits};
+use std::{
+    sync::mpsc::{Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Default latency timer for dmx output.
+///
+/// FTDI chips default to a latency timer of 16ms. The chip holds back data until either its
+/// buffer is full or the timer elapses, so with the default several frames get batched into
+/// a single USB transfer. At roughly 40 frames per second this shows up as visible jitter
+/// on the fixtures. Lighting software usually lowers the timer to 1-2ms.
+pub const DEFAULT_LATENCY_TIMER: Duration = Duration::from_millis(2);
+
+/// Default USB transfer size for dmx output. Smaller transfers keep the USB overhead per frame low.
+pub const DEFAULT_USB_TRANSFER_SIZE: u32 = 512;
+
+/// Default time the background thread waits after opening the device, before the first frame
+/// is transmitted (see OpenDmxBuilder::settle_delay()).
+pub const DEFAULT_SETTLE_DELAY: Duration = Duration::from_millis(1000);
+
+/// Default number of attempts for the calls of reset(), that fail intermittently (see
+/// OpenDmxBuilder::reset_attempts()).
+pub const DEFAULT_RESET_ATTEMPTS: u32 = 3;
+
+/// USB transfer sizes have to be a multiple of this value.
+const USB_TRANSFER_SIZE_STEP: u32 = 64;
+
+/// Maximum USB transfer size supported by the ftdi driver.
+const MAX_USB_TRANSFER_SIZE: u32 = 64 * 1024;
+
+/// Flow control of the ftdi chip.
+///
+/// The Enttec Open DMX USB and most FT232R based clones work without flow control, which is
+/// the default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FlowControl {
+    #[default]
+    None,
+    RtsCts,
+    DtrDsr,
+    XonXoff {
+        xon: u8,
+        xoff: u8,
+    },
+}
+
+/// State of the RTS line after a reset.
+///
+/// The Enttec Open DMX USB expects RTS to be cleared, which is the default. Some clones use
+/// RTS to enable their line driver and need it to be set.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RtsState {
+    #[default]
+    Clear,
+    Set,
+}
+
+/// Configuration for an open dmx device. Use it to adjust the device settings before the device
+/// is opened, either directly (see build()) or in a background thread (see run()).
+#[derive(Debug, Clone)]
+pub struct OpenDmxBuilder {
+    device_id: i32,
+    // None uses the value of the device profile.
+    latency_timer: Option<Duration>,
+    usb_transfer_size: Option<Option<u32>>,
+    flow_control: FlowControl,
+    rts: RtsState,
+    settle_delay: Duration,
+    break_time: Duration,
+    mark_after_break: Duration,
+    mark_before_break: Duration,
+    // Validated on build, if the timing has been set as a whole.
+    timing: Option<Timing>,
+    soft_start: Option<Duration>,
+    update_frequency: UpdateFrequency,
+    frame_size: FrameSize,
+    max_channels: usize,
+    recent_frames: usize,
+    latch_frames: usize,
+    refresh_mode: RefreshMode,
+    channel_profile: ChannelProfile,
+    on_control_loss: OnControlLoss,
+    reset_mode: ResetMode,
+    reset_attempts: u32,
+    #[cfg(feature = "rdm")]
+    rdm_turnaround: RdmTurnaround,
+    #[cfg(feature = "core_affinity")]
+    core: Option<usize>,
+}
+
+impl OpenDmxBuilder {
+    /// Create a new configuration with default settings for the device with the given id.
+    pub fn new(device_id: i32) -> Self {
+        OpenDmxBuilder {
+            device_id,
+            latency_timer: None,
+            usb_transfer_size: None,
+            flow_control: FlowControl::None,
+            rts: RtsState::Clear,
+            settle_delay: DEFAULT_SETTLE_DELAY,
+            break_time: Duration::from_micros(DMX_BREAK),
+            mark_after_break: Duration::from_micros(DMX_MAB),
+            mark_before_break: Duration::ZERO,
+            timing: None,
+            soft_start: None,
+            update_frequency: UpdateFrequency::DEFAULT,
+            frame_size: FrameSize::Full512,
+            max_channels: DMX_UNIVERSE_SIZE,
+            recent_frames: 0,
+            latch_frames: 1,
+            refresh_mode: RefreshMode::Steady,
+            channel_profile: ChannelProfile::default(),
+            on_control_loss: OnControlLoss::Stop,
+            reset_mode: ResetMode::Zero,
+            reset_attempts: DEFAULT_RESET_ATTEMPTS,
+            #[cfg(feature = "rdm")]
+            rdm_turnaround: RdmTurnaround::Automatic,
+            #[cfg(feature = "core_affinity")]
+            core: None,
+        }
+    }
+
+    /// Set the latency timer of the ftdi chip. The value is applied on every reset().
+    /// Defaults to the value of the device profile (see DeviceProfile).
+    pub fn latency_timer(mut self, latency_timer: Duration) -> Self {
+        self.latency_timer = Some(latency_timer);
+        self
+    }
+
+    /// Set the USB transfer size in bytes. The value is applied on every reset(), None keeps the
+    /// driver defaults. The size has to be a multiple of 64 between 64 and 65536 bytes.
+    /// Defaults to the value of the device profile (see DeviceProfile).
+    ///
+    /// The driver uses the same size for incoming and outgoing transfers, since changing the
+    /// outgoing transfer size is not supported by the D2XX driver.
+    pub fn usb_transfer_size(mut self, usb_transfer_size: Option<u32>) -> Self {
+        self.usb_transfer_size = Some(usb_transfer_size);
+        self
+    }
+
+    /// Set the flow control. The value is applied on every reset(). Defaults to none.
+    pub fn flow_control(mut self, flow_control: FlowControl) -> Self {
+        self.flow_control = flow_control;
+        self
+    }
+
+    /// Set the state of the RTS line. The value is applied on every reset(). Defaults to clear.
+    pub fn rts(mut self, rts: RtsState) -> Self {
+        self.rts = rts;
+        self
+    }
+
+    /// Set how the line is turned around to receive RDM responses (see RdmTurnaround). Defaults
+    /// to RdmTurnaround::Automatic.
+    #[cfg(feature = "rdm")]
+    pub fn rdm_turnaround(mut self, turnaround: RdmTurnaround) -> Self {
+        self.rdm_turnaround = turnaround;
+        self
+    }
+
+    /// Set the time the background thread started by run() waits after opening the device,
+    /// before the first frame is transmitted. Defaults to DEFAULT_SETTLE_DELAY.
+    ///
+    /// Use Duration::ZERO only for devices, that are already initialized. A cold device might
+    /// transmit malformed frames for a short time after it has been opened.
+    pub fn settle_delay(mut self, settle_delay: Duration) -> Self {
+        self.settle_delay = settle_delay;
+        self
+    }
+
+    /// Set the time the background thread started by run() holds the line idle (mark) after a
+    /// frame has been written and before the next break. Some fixtures with tight timing windows
+    /// drop frames without it. Defaults to zero.
+    ///
+    /// The mark is part of the frame interval (see OpenDMX::frame_interval()). If break, mark
+    /// after break, the frame itself and the mark before break take longer than the interval,
+    /// the actual frame rate drops below the update frequency.
+    pub fn mark_before_break(mut self, mark_before_break: Duration) -> Self {
+        self.mark_before_break = mark_before_break;
+        self
+    }
+
+    /// Set all timing settings at once (see Timing). The timing is checked against the dmx
+    /// standard when the device is built. Setters called afterwards override single values
+    /// without being checked.
+    pub fn timing(mut self, timing: Timing) -> Self {
+        self.break_time = timing.break_time;
+        self.mark_after_break = timing.mark_after_break;
+        self.mark_before_break = timing.mark_before_break;
+        self.settle_delay = timing.settle_delay;
+        self.latency_timer = Some(timing.latency_timer);
+        self.update_frequency = timing.update_frequency;
+        self.timing = Some(timing);
+        self
+    }
+
+    /// Ramp the intensity channels from blackout up to their values over the given duration,
+    /// when the background thread started by run() begins to transmit. Jumping to the stored
+    /// values at once can trip breakers on tungsten rigs. Defaults to none.
+    ///
+    /// This only affects the first frames after the device has been opened, later changes are
+    /// transmitted immediately.
+    pub fn soft_start(mut self, soft_start: Option<Duration>) -> Self {
+        self.soft_start = soft_start;
+        self
+    }
+
+    /// Set the number of frames the background thread started by run() transmits (see
+    /// UpdateFrequency). Defaults to UpdateFrequency::DEFAULT.
+    pub fn update_frequency(mut self, update_frequency: UpdateFrequency) -> Self {
+        self.update_frequency = update_frequency;
+        self
+    }
+
+    /// Set the number of channels transmitted with every frame (see FrameSize). Defaults to
+    /// FrameSize::Full512.
+    pub fn frame_size(mut self, frame_size: FrameSize) -> Self {
+        self.frame_size = frame_size;
+        self
+    }
+
+    /// Set the number of channels the buffer holds, between DMX_UNIVERSE_SIZE and
+    /// DMX_MAX_CHANNELS. Defaults to DMX_UNIVERSE_SIZE.
+    ///
+    /// Standard dmx carries 512 channels and every transmitted frame still does. Higher channels
+    /// can be set and read like the others, but are not transmitted yet. This is experimental
+    /// and reserved for setups chaining several frames.
+    pub fn max_channels(mut self, channels: usize) -> Self {
+        self.max_channels = channels;
+        self
+    }
+
+    /// Set the number of transmitted frames the background thread started by run() keeps for
+    /// debugging (see OpenDmxProtocol::GetRecentFrames). Each frame takes 512 bytes. Defaults to
+    /// zero, which keeps none.
+    pub fn recent_frames(mut self, recent_frames: usize) -> Self {
+        self.recent_frames = recent_frames;
+        self
+    }
+
+    /// Set the number of consecutive frames the background thread started by run() transmits a
+    /// changed buffer, before it applies the next command that changes the buffer. Some fixtures
+    /// need a few identical frames to latch a new value. Commands, that arrive in the meantime,
+    /// are delayed, not dropped. Defaults to 1, zero is treated as 1. send_once_with() takes the
+    /// number of frames directly.
+    pub fn latch_frames(mut self, frames: usize) -> Self {
+        self.latch_frames = frames.max(1);
+        self
+    }
+
+    /// Set how often the background thread started by run() transmits frames (see RefreshMode).
+    /// Defaults to RefreshMode::Steady.
+    pub fn refresh_mode(mut self, refresh_mode: RefreshMode) -> Self {
+        self.refresh_mode = refresh_mode;
+        self
+    }
+
+    /// Set the kind of every channel (see OpenDMX::set_channel_profile()). Defaults to all
+    /// channels being intensity channels.
+    pub fn channel_profile(mut self, channel_profile: ChannelProfile) -> Self {
+        self.channel_profile = channel_profile;
+        self
+    }
+
+    /// Set what the background thread started by run() does, once all command senders have been
+    /// dropped (see OnControlLoss). Defaults to OnControlLoss::Stop.
+    pub fn on_control_loss(mut self, on_control_loss: OnControlLoss) -> Self {
+        self.on_control_loss = on_control_loss;
+        self
+    }
+
+    /// Set the values OpenDMX::reset_buffer() and the blackout frame on drop set the channels
+    /// to (see ResetMode). Defaults to ResetMode::Zero.
+    pub fn reset_mode(mut self, reset_mode: ResetMode) -> Self {
+        self.reset_mode = reset_mode;
+        self
+    }
+
+    /// Set how often reset() tries the calls, that fail intermittently on some USB hubs (setting
+    /// the baud rate and purging the queues), before it gives up. The delay between two attempts
+    /// doubles every time. Defaults to DEFAULT_RESET_ATTEMPTS, values below 1 are treated as 1.
+    pub fn reset_attempts(mut self, reset_attempts: u32) -> Self {
+        self.reset_attempts = reset_attempts.max(1);
+        self
+    }
+
+    /// Pin the worker thread started by run() to the given cpu core. Keeping the thread on one
+    /// core improves the precision of the busy waits between frames on loaded systems.
+    #[cfg(feature = "core_affinity")]
+    pub fn pin_to_core(mut self, core: usize) -> Self {
+        self.core = Some(core);
+        self
+    }
+
+    /// Pin the calling thread to the configured core. If that fails, the thread keeps running
+    /// unpinned. Does nothing without the "core_affinity" feature.
+    pub(crate) fn pin_current_thread(&self) {
+        #[cfg(feature = "core_affinity")]
+        if let Some(core) = self.core {
+            let pinned = core_affinity::get_core_ids()
+                .and_then(|ids| ids.into_iter().find(|id| id.id == core))
+                .map(core_affinity::set_for_current)
+                .unwrap_or(false);
+
+            if !pinned {
+                log::warn!(
+                    "Could not pin dmx thread to core {}. Running unpinned.",
+                    core
+                );
+            }
+        }
+    }
+
+    /// Open the device with the current configuration.
+    pub fn build(self) -> Result<OpenDMX, DmxError> {
+        if let Some(Some(size)) = self.usb_transfer_size {
+            if !(USB_TRANSFER_SIZE_STEP..=MAX_USB_TRANSFER_SIZE).contains(&size)
+                || size % USB_TRANSFER_SIZE_STEP != 0
+            {
+                return Err(DmxError::InvalidTransferSize(size));
+            }
+        }
+
+        let ft = match Ftdi::with_index(self.device_id) {
+            Ok(d) => d,
+            Err(e) => {
+                return Err(DmxError::Ftdi("open ftdi device", e).or_not_found());
+            }
+        };
+
+        self.build_with_backend(Box::new(ft))
+    }
+
+    /// Same as build() but keeps trying to open the device every poll interval, until it succeeds
+    /// or the timeout elapses. On a cold boot the device might not be enumerated yet, when the
+    /// application starts. Returns the last error on time out. Invalid settings fail at once.
+    pub fn build_with_retry(self, timeout: Duration, poll: Duration) -> Result<OpenDMX, DmxError> {
+        let start = Instant::now();
+        loop {
+            match self.clone().build() {
+                Ok(device) => return Ok(device),
+                // The device is not enumerated yet or still held by another process.
+                Err(
+                    e @ (DmxError::DeviceNotFound | DmxError::Ftdi(_, FtStatus::DEVICE_NOT_OPENED)),
+                ) => {
+                    if start.elapsed() + poll > timeout {
+                        return Err(e);
+                    }
+                    log::debug!("Could not open dmx device {}. Retrying.", self.device_id);
+                    thread::sleep(poll);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Create the device with the current configuration for an already opened backend.
+    pub fn build_with_backend(self, mut backend: Box<dyn DmxBackend>) -> Result<OpenDMX, DmxError> {
+        self.frame_size.validate()?;
+        self.refresh_mode.validate()?;
+        if !(DMX_UNIVERSE_SIZE..=DMX_MAX_CHANNELS).contains(&self.max_channels) {
+            return Err(DmxError::InvalidMaxChannels(self.max_channels));
+        }
+        if let Some(timing) = self.timing {
+            timing.validate()?;
+        }
+
+        let device_info = match backend.device_info() {
+            Ok(d) => d,
+            Err(e) => {
+                return Err(DmxError::Ftdi("read device info", e));
+            }
+        };
+
+        let profile = DeviceProfile::for_device_type(device_info.device_type);
+        let driver_version = backend.driver_version().ok();
+        let library_version = OpenDMX::library_version().ok();
+
+        Ok(OpenDMX {
+            backend,
+            state: ConnectionState::Opened,
+            buffer: vec![0; self.max_channels + 1].into_boxed_slice(),
+            dirty: false,
+            info: device_info,
+            baud_rate: profile.baud_rate,
+            bits_per_word: libftd2xx::BitsPerWord::Bits8,
+            stop_bits: StopBits::Bits2,
+            read_time_out: Duration::from_millis(500),
+            write_time_out: Duration::from_millis(500),
+            parity_none: libftd2xx::Parity::No,
+            latency_timer: self.latency_timer.unwrap_or(profile.latency_timer),
+            usb_transfer_size: self.usb_transfer_size.unwrap_or(profile.usb_transfer_size),
+            flow_control: self.flow_control,
+            rts: self.rts,
+            settle_delay: self.settle_delay,
+            break_time: self.break_time,
+            mark_after_break: self.mark_after_break,
+            mark_before_break: self.mark_before_break,
+            curve: IntensityCurve::Linear,
+            channel_profile: self.channel_profile,
+            soft_start: self.soft_start,
+            ramp_level: u8::MAX,
+            layers: Layers::default(),
+            driver_version,
+            library_version,
+            update_frequency: self.update_frequency,
+            frame_size: self.frame_size,
+            on_frame: None,
+            observers: Vec::new(),
+            shared_universe: None,
+            recent_frames: self.recent_frames,
+            latch_frames: self.latch_frames,
+            refresh_mode: self.refresh_mode,
+            on_control_loss: self.on_control_loss,
+            reset_attempts: self.reset_attempts,
+            blackout_on_drop: true,
+            reset_mode: self.reset_mode,
+            #[cfg(feature = "rdm")]
+            rdm_turnaround: self.rdm_turnaround,
+        })
+    }
+
+    /// Open the device with the current configuration in a background thread, that continuously
+    /// updates the device. See OpenDMX::run().
+    pub fn run(
+        self,
+    ) -> (
+        Sender<OpenDmxProtocol>,
+        Receiver<OpenDmxProtocol>,
+        SharedSnapshot,
+    ) {
+        OpenDMX::run_with(self)
+    }
+}
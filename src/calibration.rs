@@ -0,0 +1,83 @@
+use crate::{DmxError, OpenDMX, UpdateFrequency};
+use std::time::{Duration, Instant};
+
+/// Share of the measured frame rate, that is recommended as update frequency. Leaves some
+/// headroom for a loaded system.
+const HEADROOM: f64 = 0.8;
+
+/// Result of OpenDMX::calibrate().
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationReport {
+    /// Number of frames transmitted during the calibration.
+    pub frames: u64,
+    pub frames_per_second: f64,
+    pub min_frame_time: Duration,
+    pub max_frame_time: Duration,
+    pub avg_frame_time: Duration,
+    /// Update frequency, that the device can hold on this machine (see OpenDMX::run()).
+    pub recommended_update_frequency: UpdateFrequency,
+}
+
+impl OpenDMX {
+    /// Open the device with the given id and transmit frames as fast as possible for the given
+    /// duration. The report tells which update frequency the device and the machine can hold.
+    pub fn calibrate(device_id: i32, duration: Duration) -> Result<CalibrationReport, DmxError> {
+        let mut device = OpenDMX::new(device_id)?;
+        device.reset()?;
+        device.calibrate_device(duration)
+    }
+
+    /// Transmit frames as fast as possible for the given duration. See calibrate().
+    pub fn calibrate_device(&mut self, duration: Duration) -> Result<CalibrationReport, DmxError> {
+        let mut frames: u64 = 0;
+        let mut min_frame_time = Duration::MAX;
+        let mut max_frame_time = Duration::ZERO;
+
+        let start = Instant::now();
+        while start.elapsed() < duration || frames == 0 {
+            let frame_start = Instant::now();
+            self.tick()?;
+            let frame_time = frame_start.elapsed();
+
+            frames += 1;
+            min_frame_time = min_frame_time.min(frame_time);
+            max_frame_time = max_frame_time.max(frame_time);
+        }
+        let elapsed = start.elapsed();
+
+        let frames_per_second = frames as f64 / elapsed.as_secs_f64();
+        let recommended = ((frames_per_second * HEADROOM).floor() as u32)
+            .saturating_mul(1000)
+            .clamp(UpdateFrequency::MIN.get(), UpdateFrequency::MAX.get());
+
+        Ok(CalibrationReport {
+            frames,
+            frames_per_second,
+            min_frame_time,
+            max_frame_time,
+            // Divide in nanoseconds, a frame count above u32::MAX must not be truncated.
+            avg_frame_time: Duration::from_nanos((elapsed.as_nanos() / frames as u128) as u64),
+            recommended_update_frequency: UpdateFrequency::new(recommended)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MockBackend;
+
+    use super::*;
+
+    #[test]
+    fn mock_calibrate_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+
+        let report = subject.calibrate_device(Duration::from_millis(50)).unwrap();
+        assert_eq!(report.frames, backend.writes().len() as u64);
+        assert!(report.min_frame_time <= report.avg_frame_time);
+        assert!(report.avg_frame_time <= report.max_frame_time);
+        assert!(report.recommended_update_frequency <= UpdateFrequency::MAX);
+    }
+}
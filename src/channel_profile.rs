@@ -0,0 +1,180 @@
+use crate::{ChannelMask, DmxError, DMX_UNIVERSE_SIZE};
+
+/// What a channel controls. Only intensity channels are scaled by the intensity curve and the
+/// soft start, moving a position or color channel would move or recolor the fixture instead.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelKind {
+    /// Dimmer or brightness of a fixture.
+    #[default]
+    Intensity,
+    /// Color mixing or color wheel.
+    Color,
+    /// Pan, tilt or any other movement.
+    Position,
+    /// Everything else, e.g. gobos, strobe or control channels.
+    Attribute,
+}
+
+/// A range of channels in the JSON format of ChannelProfile::from_json().
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ChannelRange {
+    start: usize,
+    len: usize,
+    kind: ChannelKind,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ChannelProfileJson {
+    channels: Vec<ChannelRange>,
+}
+
+/// The kind of every channel of a universe (see OpenDmxBuilder::channel_profile()). All
+/// channels are intensity channels by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelProfile {
+    kinds: [ChannelKind; DMX_UNIVERSE_SIZE],
+}
+
+impl Default for ChannelProfile {
+    fn default() -> Self {
+        ChannelProfile {
+            kinds: [ChannelKind::Intensity; DMX_UNIVERSE_SIZE],
+        }
+    }
+}
+
+impl ChannelProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a profile from JSON. Channels, that are not listed, are intensity channels, e.g.
+    /// `{"channels": [{"start": 1, "len": 2, "kind": "Position"}]}`.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let parsed: ChannelProfileJson = serde_json::from_str(json)?;
+
+        let mut profile = ChannelProfile::new();
+        for range in parsed.channels {
+            if profile
+                .set_range(range.start, range.len, range.kind)
+                .is_err()
+            {
+                return Err(serde::de::Error::custom("channel range out of bounds"));
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Set the kind of the given channel.
+    pub fn set(&mut self, channel: usize, kind: ChannelKind) -> Result<(), DmxError> {
+        if channel == 0 || channel > DMX_UNIVERSE_SIZE {
+            return Err(DmxError::InvalidChannel(channel));
+        }
+        self.kinds[channel - 1] = kind;
+
+        Ok(())
+    }
+
+    /// Set the kind of len channels beginning with start. Fails with the first channel, that
+    /// is out of range.
+    pub fn set_range(
+        &mut self,
+        start: usize,
+        len: usize,
+        kind: ChannelKind,
+    ) -> Result<(), DmxError> {
+        if start == 0 {
+            return Err(DmxError::InvalidChannel(start));
+        }
+        let last = start.saturating_add(len) - 1;
+        if last > DMX_UNIVERSE_SIZE {
+            return Err(DmxError::InvalidChannel(start.max(DMX_UNIVERSE_SIZE + 1)));
+        }
+        self.kinds[start - 1..last].fill(kind);
+
+        Ok(())
+    }
+
+    /// The kind of the given channel.
+    pub fn kind(&self, channel: usize) -> Result<ChannelKind, DmxError> {
+        if channel == 0 || channel > DMX_UNIVERSE_SIZE {
+            return Err(DmxError::InvalidChannel(channel));
+        }
+        Ok(self.kinds[channel - 1])
+    }
+
+    /// Whether the given slot of a frame holds an intensity channel. The start code is not.
+    pub(crate) fn is_intensity(&self, slot: usize) -> bool {
+        self.kind(slot) == Ok(ChannelKind::Intensity)
+    }
+
+    /// All channels of the given kind.
+    pub fn mask(&self, kind: ChannelKind) -> ChannelMask {
+        let mut mask = ChannelMask::empty();
+        for (index, channel_kind) in self.kinds.iter().enumerate() {
+            if *channel_kind == kind {
+                // Every index is a valid channel.
+                let _ = mask.insert(index + 1);
+            }
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_profile_test() {
+        let mut subject = ChannelProfile::new();
+        subject.set_range(1, 2, ChannelKind::Position).unwrap();
+        subject.set(DMX_UNIVERSE_SIZE, ChannelKind::Color).unwrap();
+        assert_eq!(
+            subject.set(0, ChannelKind::Color),
+            Err(DmxError::InvalidChannel(0))
+        );
+        assert_eq!(
+            subject.set_range(DMX_UNIVERSE_SIZE, 2, ChannelKind::Color),
+            Err(DmxError::InvalidChannel(DMX_UNIVERSE_SIZE + 1))
+        );
+
+        assert_eq!(subject.kind(2), Ok(ChannelKind::Position));
+        assert_eq!(subject.kind(3), Ok(ChannelKind::Intensity));
+        assert_eq!(
+            subject.kind(DMX_UNIVERSE_SIZE + 1),
+            Err(DmxError::InvalidChannel(DMX_UNIVERSE_SIZE + 1))
+        );
+        assert!(!subject.is_intensity(0));
+        assert!(!subject.is_intensity(1));
+        assert!(subject.is_intensity(3));
+
+        let position = subject.mask(ChannelKind::Position);
+        assert!(position.contains(1));
+        assert!(position.contains(2));
+        assert!(!position.contains(3));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn channel_profile_json_test() {
+        let json = r#"{"channels": [
+            {"start": 1, "len": 2, "kind": "Position"},
+            {"start": 512, "len": 1, "kind": "Color"}
+        ]}"#;
+        let subject = ChannelProfile::from_json(json).unwrap();
+        assert_eq!(subject.kind(1), Ok(ChannelKind::Position));
+        assert_eq!(subject.kind(3), Ok(ChannelKind::Intensity));
+        assert_eq!(subject.kind(512), Ok(ChannelKind::Color));
+
+        assert!(ChannelProfile::from_json("{}").is_err());
+        assert!(ChannelProfile::from_json(
+            r#"{"channels": [{"start": 0, "len": 1, "kind": "Color"}]}"#
+        )
+        .is_err());
+    }
+}
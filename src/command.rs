@@ -0,0 +1,241 @@
+use crate::{
+    CueList, DmxError, EventKind, FrameSize, FrameStats, MergeMode, OpenDMX, OpenDmxBuilder,
+    OpenDmxProtocol, SharedSnapshot, TestPattern, TimingStats, UniverseSnapshot, DMX_UNIVERSE_SIZE,
+};
+use libftd2xx::{DeviceInfo, DeviceStatus};
+use std::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, RwLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Commands for the background thread started by OpenDMX::run_typed(). The variants match the
+/// commands of OpenDmxProtocol.
+#[derive(Debug)]
+pub enum DmxCommand {
+    SetValue(usize, u8),
+    Bump(usize, i16),
+    SendFrame(Box<[u8; DMX_UNIVERSE_SIZE]>),
+    SetLayerValue(usize, usize, u8),
+    SetLayerMode(usize, MergeMode),
+    ClearLayer(usize),
+    Delta(Vec<(usize, u8)>),
+    Stop,
+    StopHold,
+    Reset,
+    ResetBuffer,
+    ResetToIdle,
+    SetAll(u8),
+    StartCode(u8),
+    FrameSize(FrameSize),
+    ResetRange(usize, usize),
+    Purge,
+    Pause,
+    Resume,
+    Idle,
+    Engage,
+    GetPhase,
+    GetRecentFrames,
+    GetStats,
+    GetEventLog,
+    ListDevices,
+    PlayCues(CueList),
+    StopCues,
+    SnapToTarget,
+    SyncClock(Instant),
+    ScheduleFrame(Instant, Box<[u8; DMX_UNIVERSE_SIZE]>),
+    TestPattern(TestPattern),
+    StopTestPattern,
+}
+
+/// Events returned from the background thread started by OpenDMX::run_typed(). The variants
+/// match the responses of OpenDmxProtocol. Once the thread has stopped, the receiver fails.
+#[derive(Debug)]
+pub enum DmxEvent {
+    RecentFrames(Vec<[u8; DMX_UNIVERSE_SIZE]>),
+    Stats(Box<FrameStats>),
+    EventLog(Vec<(Instant, EventKind)>),
+    DeviceList(Vec<DeviceInfo>),
+    Started(Duration),
+    Phase { elapsed: Duration, frame: Duration },
+    Paused,
+    Resumed,
+    CueStarted(usize),
+    LineError(DeviceStatus),
+    TimingDegraded(Box<TimingStats>),
+    Error(Box<DmxError>),
+}
+
+impl From<DmxCommand> for OpenDmxProtocol {
+    fn from(cmd: DmxCommand) -> Self {
+        match cmd {
+            DmxCommand::SetValue(channel, value) => OpenDmxProtocol::SetValue(channel, value),
+            DmxCommand::Bump(channel, delta) => OpenDmxProtocol::Bump(channel, delta),
+            DmxCommand::SendFrame(frame) => OpenDmxProtocol::SendFrame(frame),
+            DmxCommand::SetLayerValue(layer, channel, value) => {
+                OpenDmxProtocol::SetLayerValue(layer, channel, value)
+            }
+            DmxCommand::SetLayerMode(layer, mode) => OpenDmxProtocol::SetLayerMode(layer, mode),
+            DmxCommand::ClearLayer(layer) => OpenDmxProtocol::ClearLayer(layer),
+            DmxCommand::Delta(delta) => OpenDmxProtocol::Delta(delta),
+            DmxCommand::Stop => OpenDmxProtocol::Stop,
+            DmxCommand::StopHold => OpenDmxProtocol::StopHold,
+            DmxCommand::Reset => OpenDmxProtocol::Reset,
+            DmxCommand::ResetBuffer => OpenDmxProtocol::ResetBuffer,
+            DmxCommand::ResetToIdle => OpenDmxProtocol::ResetToIdle,
+            DmxCommand::SetAll(value) => OpenDmxProtocol::SetAll(value),
+            DmxCommand::StartCode(start_code) => OpenDmxProtocol::StartCode(start_code),
+            DmxCommand::FrameSize(frame_size) => OpenDmxProtocol::FrameSize(frame_size),
+            DmxCommand::ResetRange(start, len) => OpenDmxProtocol::ResetRange(start, len),
+            DmxCommand::Purge => OpenDmxProtocol::Purge,
+            DmxCommand::Pause => OpenDmxProtocol::Pause,
+            DmxCommand::Resume => OpenDmxProtocol::Resume,
+            DmxCommand::Idle => OpenDmxProtocol::Idle,
+            DmxCommand::Engage => OpenDmxProtocol::Engage,
+            DmxCommand::GetPhase => OpenDmxProtocol::GetPhase,
+            DmxCommand::GetRecentFrames => OpenDmxProtocol::GetRecentFrames,
+            DmxCommand::GetStats => OpenDmxProtocol::GetStats,
+            DmxCommand::GetEventLog => OpenDmxProtocol::GetEventLog,
+            DmxCommand::ListDevices => OpenDmxProtocol::ListDevices,
+            DmxCommand::PlayCues(list) => OpenDmxProtocol::PlayCues(list),
+            DmxCommand::StopCues => OpenDmxProtocol::StopCues,
+            DmxCommand::SnapToTarget => OpenDmxProtocol::SnapToTarget,
+            DmxCommand::SyncClock(epoch) => OpenDmxProtocol::SyncClock(epoch),
+            DmxCommand::ScheduleFrame(at, frame) => OpenDmxProtocol::ScheduleFrame(at, frame),
+            DmxCommand::TestPattern(pattern) => OpenDmxProtocol::TestPattern(pattern),
+            DmxCommand::StopTestPattern => OpenDmxProtocol::StopTestPattern,
+        }
+    }
+}
+
+/// Fails with the given message, if it is not a response.
+impl TryFrom<OpenDmxProtocol> for DmxEvent {
+    type Error = OpenDmxProtocol;
+
+    fn try_from(msg: OpenDmxProtocol) -> Result<Self, OpenDmxProtocol> {
+        match msg {
+            OpenDmxProtocol::RecentFrames(frames) => Ok(DmxEvent::RecentFrames(frames)),
+            OpenDmxProtocol::Stats(stats) => Ok(DmxEvent::Stats(stats)),
+            OpenDmxProtocol::EventLog(events) => Ok(DmxEvent::EventLog(events)),
+            OpenDmxProtocol::DeviceList(list) => Ok(DmxEvent::DeviceList(list)),
+            OpenDmxProtocol::Started(frame) => Ok(DmxEvent::Started(frame)),
+            OpenDmxProtocol::Phase { elapsed, frame } => Ok(DmxEvent::Phase { elapsed, frame }),
+            OpenDmxProtocol::Paused => Ok(DmxEvent::Paused),
+            OpenDmxProtocol::Resumed => Ok(DmxEvent::Resumed),
+            OpenDmxProtocol::CueStarted(index) => Ok(DmxEvent::CueStarted(index)),
+            OpenDmxProtocol::LineError(status) => Ok(DmxEvent::LineError(status)),
+            OpenDmxProtocol::TimingDegraded(stats) => Ok(DmxEvent::TimingDegraded(stats)),
+            OpenDmxProtocol::Error(e) => Ok(DmxEvent::Error(e)),
+            other => Err(other),
+        }
+    }
+}
+
+/// The channel the background thread returns its responses through.
+pub(crate) trait ResponseSender {
+    fn notify(&self, msg: OpenDmxProtocol) -> Result<(), DmxError>;
+}
+
+impl ResponseSender for Sender<OpenDmxProtocol> {
+    fn notify(&self, msg: OpenDmxProtocol) -> Result<(), DmxError> {
+        match self.send(msg) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(DmxError::Disconnected),
+        }
+    }
+}
+
+/// Discards all responses, for threads without a response channel (see OpenDMX::run_shared()).
+pub(crate) struct NoResponses;
+
+impl ResponseSender for NoResponses {
+    fn notify(&self, _msg: OpenDmxProtocol) -> Result<(), DmxError> {
+        Ok(())
+    }
+}
+
+impl ResponseSender for Sender<DmxEvent> {
+    fn notify(&self, msg: OpenDmxProtocol) -> Result<(), DmxError> {
+        // The thread only responds with events.
+        let event = match DmxEvent::try_from(msg) {
+            Ok(e) => e,
+            Err(_) => return Ok(()),
+        };
+
+        match self.send(event) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(DmxError::Disconnected),
+        }
+    }
+}
+
+impl OpenDMX {
+    /// Same as run() but commands and responses use separate types, so matching the responses
+    /// only has to handle the events the thread actually sends.
+    pub fn run_typed(id: i32) -> (Sender<DmxCommand>, Receiver<DmxEvent>, SharedSnapshot) {
+        let builder = OpenDmxBuilder::new(id);
+        let (sender, receiver) = mpsc::channel();
+        let (sender2, receiver2) = mpsc::channel();
+
+        let snapshot: SharedSnapshot = Arc::new(RwLock::new(UniverseSnapshot::default()));
+        let snapshot2 = snapshot.clone();
+
+        thread::spawn(move || {
+            builder.pin_current_thread();
+
+            let device = match builder.build() {
+                Ok(d) => d,
+                Err(e) => return Self::report_error(&sender2, e),
+            };
+            Self::worker(device, receiver, sender2, snapshot2);
+        });
+
+        (sender, receiver2, snapshot)
+    }
+
+    /// Same as run_typed() but uses this already opened device.
+    pub fn spawn_typed(self) -> (Sender<DmxCommand>, Receiver<DmxEvent>, SharedSnapshot) {
+        let (sender, receiver) = mpsc::channel();
+        let (sender2, receiver2) = mpsc::channel();
+
+        let snapshot: SharedSnapshot = Arc::new(RwLock::new(UniverseSnapshot::default()));
+        let snapshot2 = snapshot.clone();
+
+        thread::spawn(move || {
+            Self::worker(self, receiver, sender2, snapshot2);
+        });
+
+        (sender, receiver2, snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockBackend;
+
+    #[test]
+    fn mock_typed_test() {
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        let (sender, receiver, _) = device.spawn_typed();
+        assert!(matches!(
+            receiver.recv_timeout(Duration::from_secs(5)),
+            Ok(DmxEvent::Started(_))
+        ));
+
+        sender.send(DmxCommand::Pause).unwrap();
+        assert!(matches!(
+            receiver.recv_timeout(Duration::from_secs(5)),
+            Ok(DmxEvent::Paused)
+        ));
+
+        sender.send(DmxCommand::Stop).unwrap();
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_err());
+    }
+}
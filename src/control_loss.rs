@@ -0,0 +1,22 @@
+/// What the background thread started by run() does, once all command senders have been
+/// dropped, e.g. because the controlling application crashed. Afterwards nobody can send
+/// commands to the thread anymore.
+///
+/// Receivers differ in what they do, when the dmx signal is lost. Many hold the last values
+/// for a while or indefinitely, others black out at once. Stop leaves this decision to the
+/// receivers, Hold and Blackout keep the signal, so the outcome does not depend on them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OnControlLoss {
+    /// Keep transmitting the last frame until the process exits. The lights stay as they were,
+    /// which keeps a show running, but also keeps moving lights, hazers or strobes going with
+    /// nobody in control.
+    Hold,
+    /// Transmit all channels at zero, or the idle state (see OpenDMX::set_reset_mode()), until
+    /// the process exits. This is the safest choice, if fixtures must not run unattended, but
+    /// ends the show on any hiccup of the application.
+    Blackout,
+    /// Send a blackout frame, close the device and stop the thread, like a Stop command. What
+    /// the fixtures do without a signal depends on the receivers.
+    #[default]
+    Stop,
+}
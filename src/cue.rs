@@ -0,0 +1,193 @@
+use crate::DMX_UNIVERSE_SIZE;
+use std::time::{Duration, Instant};
+
+/// A scene of a cue list. The scene is faded in over fade and held for hold before the next cue
+/// starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    /// Values of all channels, where frame[0] is channel 1.
+    pub frame: Box<[u8; DMX_UNIVERSE_SIZE]>,
+    pub fade: Duration,
+    pub hold: Duration,
+}
+
+/// Scenes played one after another by the run() thread (see OpenDmxProtocol::PlayCues).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CueList {
+    pub cues: Vec<Cue>,
+}
+
+impl CueList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a cue.
+    pub fn push(&mut self, frame: [u8; DMX_UNIVERSE_SIZE], fade: Duration, hold: Duration) {
+        self.cues.push(Cue {
+            frame: Box::new(frame),
+            fade,
+            hold,
+        });
+    }
+}
+
+/// State of the cue list, that is being played.
+pub(crate) struct CuePlayer {
+    list: CueList,
+    index: usize,
+    started: Instant,
+    /// Values at the start of the current cue.
+    from: [u8; DMX_UNIVERSE_SIZE],
+}
+
+/// Result of CuePlayer::update().
+pub(crate) struct CueStep {
+    pub(crate) frame: [u8; DMX_UNIVERSE_SIZE],
+    /// Index of the cue, that has been started by this update, if any.
+    pub(crate) started: Option<usize>,
+    /// Whether the last cue has been held for its hold time.
+    pub(crate) finished: bool,
+}
+
+impl CuePlayer {
+    /// Start the given list with a fade from the given frame. Returns None for an empty list.
+    pub(crate) fn new(list: CueList, from: [u8; DMX_UNIVERSE_SIZE], now: Instant) -> Option<Self> {
+        if list.cues.is_empty() {
+            return None;
+        }
+
+        Some(CuePlayer {
+            list,
+            index: 0,
+            started: now,
+            from,
+        })
+    }
+
+    /// End the fade of the current cue at the given point in time, so the next update returns
+    /// the target of the cue. The hold time starts now. Does nothing, if the cue is not fading.
+    pub(crate) fn snap(&mut self, now: Instant) {
+        let fade = self.list.cues[self.index].fade;
+        if now.duration_since(self.started) >= fade {
+            return;
+        }
+
+        if let Some(started) = now.checked_sub(fade) {
+            self.started = started;
+        }
+    }
+
+    /// Compute the frame for the given point in time and advance to the next cue if necessary.
+    pub(crate) fn update(&mut self, now: Instant) -> CueStep {
+        let mut started = None;
+        loop {
+            let cue = &self.list.cues[self.index];
+            let length = cue.fade + cue.hold;
+            if now.duration_since(self.started) < length || self.index + 1 == self.list.cues.len() {
+                break;
+            }
+
+            self.from = *cue.frame;
+            self.started += length;
+            self.index += 1;
+            started = Some(self.index);
+        }
+
+        let cue = &self.list.cues[self.index];
+        let elapsed = now.duration_since(self.started);
+        let frame = if elapsed >= cue.fade {
+            *cue.frame
+        } else {
+            let ratio = elapsed.as_secs_f64() / cue.fade.as_secs_f64();
+            let mut frame = [0; DMX_UNIVERSE_SIZE];
+            for (value, (from, to)) in frame.iter_mut().zip(self.from.iter().zip(cue.frame.iter()))
+            {
+                let delta = (*to as f64 - *from as f64) * ratio;
+                *value = (*from as f64 + delta).round() as u8;
+            }
+            frame
+        };
+
+        CueStep {
+            frame,
+            started,
+            finished: elapsed >= cue.fade + cue.hold,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cue_player_test() {
+        let mut list = CueList::new();
+        list.push(
+            [200; DMX_UNIVERSE_SIZE],
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+        );
+        list.push(
+            [0; DMX_UNIVERSE_SIZE],
+            Duration::ZERO,
+            Duration::from_secs(1),
+        );
+
+        let start = Instant::now();
+        assert!(CuePlayer::new(CueList::new(), [0; DMX_UNIVERSE_SIZE], start).is_none());
+        let mut subject = CuePlayer::new(list, [0; DMX_UNIVERSE_SIZE], start).unwrap();
+
+        let step = subject.update(start + Duration::from_secs(1));
+        assert_eq!(step.frame[0], 100);
+        assert_eq!(step.started, None);
+
+        let step = subject.update(start + Duration::from_millis(2500));
+        assert_eq!(step.frame[0], 200);
+
+        let step = subject.update(start + Duration::from_millis(3500));
+        assert_eq!(step.frame[0], 0);
+        assert_eq!(step.started, Some(1));
+        assert!(!step.finished);
+
+        let step = subject.update(start + Duration::from_secs(4));
+        assert_eq!(step.started, None);
+        assert!(step.finished);
+    }
+
+    #[test]
+    fn cue_player_snap_test() {
+        let mut list = CueList::new();
+        list.push(
+            [200; DMX_UNIVERSE_SIZE],
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+        );
+        list.push(
+            [0; DMX_UNIVERSE_SIZE],
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+        );
+
+        let start = Instant::now();
+        let mut subject = CuePlayer::new(list, [0; DMX_UNIVERSE_SIZE], start).unwrap();
+        subject.snap(start + Duration::from_secs(1));
+        assert_eq!(subject.update(start + Duration::from_secs(1)).frame[0], 200);
+
+        // The hold time starts with the snap.
+        let step = subject.update(start + Duration::from_millis(1900));
+        assert_eq!(step.frame[0], 200);
+        let step = subject.update(start + Duration::from_millis(2500));
+        assert_eq!(step.started, Some(1));
+
+        // A cue, that is not fading anymore, is not changed.
+        subject.snap(start + Duration::from_millis(4500));
+        assert_eq!(
+            subject.update(start + Duration::from_millis(4500)).frame[0],
+            0
+        );
+        subject.snap(start + Duration::from_millis(4600));
+        assert!(subject.update(start + Duration::from_millis(5000)).finished);
+    }
+}
@@ -0,0 +1,41 @@
+/// Maps the values stored in the buffer to the values being transmitted. Dimmers usually do not
+/// respond linearly, so a curve can make a fader feel perceptually even.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum IntensityCurve {
+    /// Transmit the stored values unchanged.
+    #[default]
+    Linear,
+    /// Transmit the square of the stored values (scaled back to 0..=255).
+    Square,
+    /// Look up the transmitted value for every stored value.
+    Table(Box<[u8; 256]>),
+}
+
+impl IntensityCurve {
+    /// Map a stored value to the transmitted value.
+    pub fn apply(&self, value: u8) -> u8 {
+        match self {
+            IntensityCurve::Linear => value,
+            IntensityCurve::Square => ((value as u32 * value as u32 + 127) / 255) as u8,
+            IntensityCurve::Table(table) => table[value as usize],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curve_test() {
+        assert_eq!(IntensityCurve::Linear.apply(100), 100);
+
+        assert_eq!(IntensityCurve::Square.apply(0), 0);
+        assert_eq!(IntensityCurve::Square.apply(128), 64);
+        assert_eq!(IntensityCurve::Square.apply(255), 255);
+
+        let mut table = [0; 256];
+        table[1] = 200;
+        assert_eq!(IntensityCurve::Table(Box::new(table)).apply(1), 200);
+    }
+}
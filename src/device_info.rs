@@ -0,0 +1,86 @@
+use libftd2xx::DeviceInfo;
+
+/// Owned information about a dmx device. Unlike libftd2xx::DeviceInfo this type does not tie
+/// applications to libftd2xx, so it can be exposed in their own API. With the "serde" feature
+/// enabled it can be serialized as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpenDmxDeviceInfo {
+    pub serial: String,
+    pub description: String,
+    /// Name of the ftdi chip, e.g. "FT232R".
+    pub device_type: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
+
+impl From<&DeviceInfo> for OpenDmxDeviceInfo {
+    fn from(info: &DeviceInfo) -> Self {
+        OpenDmxDeviceInfo {
+            serial: info.serial_number.clone(),
+            description: info.description.clone(),
+            device_type: format!("{:?}", info.device_type),
+            vendor_id: info.vendor_id,
+            product_id: info.product_id,
+        }
+    }
+}
+
+/// A connected device as listed by OpenDMX::enumerate().
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceEntry {
+    /// The device id to open the device with, e.g. with OpenDMX::new().
+    pub index: i32,
+    pub serial: String,
+    pub description: String,
+    /// Name of the ftdi chip, e.g. "FT232R".
+    pub device_type: String,
+    /// Whether the device is already opened, by this or another process.
+    pub busy: bool,
+}
+
+impl DeviceEntry {
+    pub(crate) fn new(index: i32, info: &DeviceInfo) -> Self {
+        DeviceEntry {
+            index,
+            serial: info.serial_number.clone(),
+            description: info.description.clone(),
+            device_type: format!("{:?}", info.device_type),
+            busy: info.port_open,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libftd2xx::DeviceType;
+
+    use super::*;
+
+    #[test]
+    fn from_device_info_test() {
+        let info = DeviceInfo {
+            port_open: true,
+            speed: None,
+            device_type: DeviceType::FT232R,
+            vendor_id: 0x0403,
+            product_id: 0x6001,
+            serial_number: "AL05O9B5".to_owned(),
+            description: "FT232R USB UART".to_owned(),
+        };
+
+        let subject = OpenDmxDeviceInfo::from(&info);
+        assert_eq!("AL05O9B5", subject.serial);
+        assert_eq!("FT232R USB UART", subject.description);
+        assert_eq!("FT232R", subject.device_type);
+        assert_eq!(0x0403, subject.vendor_id);
+        assert_eq!(0x6001, subject.product_id);
+
+        let entry = DeviceEntry::new(2, &info);
+        assert_eq!(2, entry.index);
+        assert_eq!("AL05O9B5", entry.serial);
+        assert_eq!("FT232R", entry.device_type);
+        assert!(entry.busy);
+    }
+}
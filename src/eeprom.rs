@@ -0,0 +1,191 @@
+use crate::{DmxError, OpenDMX, Timing, UpdateFrequency};
+use std::time::Duration;
+
+/// Marks the user area as holding an open_dmx configuration.
+const MAGIC: &[u8; 4] = b"ODMX";
+/// Version of the layout below.
+const VERSION: u8 = 1;
+/// Magic, version, update frequency (u32), break and mark after break in microseconds (u16 each)
+/// and the checksum. All numbers are little endian.
+const CONFIG_LEN: usize = 14;
+
+/// Settings stored in the user area of the EEPROM of a device, so a device can be configured
+/// once and carries its preferred settings to every application (see
+/// OpenDMX::read_eeprom_config()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EepromConfig {
+    pub update_frequency: UpdateFrequency,
+    pub break_time: Duration,
+    pub mark_after_break: Duration,
+}
+
+impl EepromConfig {
+    /// The stored settings completed with the standard timing, e.g. for
+    /// OpenDmxBuilder::timing().
+    pub fn timing(&self) -> Timing {
+        Timing {
+            break_time: self.break_time,
+            mark_after_break: self.mark_after_break,
+            update_frequency: self.update_frequency,
+            ..Timing::dmx_standard()
+        }
+    }
+
+    fn encode(&self) -> Result<[u8; CONFIG_LEN], DmxError> {
+        self.timing().validate()?;
+
+        let break_time = Self::micros(self.break_time, "break")?;
+        let mark_after_break = Self::micros(self.mark_after_break, "mark after break")?;
+
+        let mut data = [0; CONFIG_LEN];
+        data[0..4].copy_from_slice(MAGIC);
+        data[4] = VERSION;
+        data[5..9].copy_from_slice(&self.update_frequency.get().to_le_bytes());
+        data[9..11].copy_from_slice(&break_time.to_le_bytes());
+        data[11..13].copy_from_slice(&mark_after_break.to_le_bytes());
+        data[13] = checksum(&data[..13]);
+        Ok(data)
+    }
+
+    /// Returns None if the data does not contain a configuration, e.g. a new device.
+    fn decode(data: &[u8; CONFIG_LEN]) -> Result<Option<Self>, DmxError> {
+        if &data[0..4] != MAGIC {
+            return Ok(None);
+        }
+        if data[4] != VERSION {
+            return Err(DmxError::Decode("unknown eeprom config version"));
+        }
+        if data[13] != checksum(&data[..13]) {
+            return Err(DmxError::Decode("eeprom config checksum"));
+        }
+
+        let frequency = u32::from_le_bytes([data[5], data[6], data[7], data[8]]);
+        let break_time = u16::from_le_bytes([data[9], data[10]]);
+        let mark_after_break = u16::from_le_bytes([data[11], data[12]]);
+
+        let config = EepromConfig {
+            update_frequency: UpdateFrequency::new(frequency)?,
+            break_time: Duration::from_micros(break_time as u64),
+            mark_after_break: Duration::from_micros(mark_after_break as u64),
+        };
+        config.timing().validate()?;
+        Ok(Some(config))
+    }
+
+    /// The given duration in microseconds. Fails if it does not fit into the layout.
+    fn micros(duration: Duration, setting: &'static str) -> Result<u16, DmxError> {
+        match u16::try_from(duration.as_micros()) {
+            Ok(micros) => Ok(micros),
+            Err(_) => Err(DmxError::InvalidTiming(setting)),
+        }
+    }
+}
+
+/// Two's complement of the sum of the given bytes, so all bytes including the checksum add up
+/// to zero.
+fn checksum(data: &[u8]) -> u8 {
+    data.iter()
+        .fold(0u8, |sum, value| sum.wrapping_add(*value))
+        .wrapping_neg()
+}
+
+impl OpenDMX {
+    /// Read the configuration stored with write_eeprom_config(). Returns None if the device
+    /// has not been configured yet.
+    pub fn read_eeprom_config(&mut self) -> Result<Option<EepromConfig>, DmxError> {
+        self.check_eeprom_user_size()?;
+
+        let mut data = [0; CONFIG_LEN];
+        match self.backend.eeprom_user_read(&mut data) {
+            Ok(CONFIG_LEN) => {}
+            Ok(_) => return Err(DmxError::Decode("short eeprom read")),
+            Err(e) => return Err(DmxError::Ftdi("read eeprom", e)),
+        }
+
+        EepromConfig::decode(&data)
+    }
+
+    /// Store the given configuration in the user area of the EEPROM and read it back to verify
+    /// it. Only the user area is written, the USB configuration of the chip is never touched, so
+    /// a failed write can not render the device unusable. Fails for timings, that violate the dmx
+    /// standard.
+    pub fn write_eeprom_config(&mut self, config: &EepromConfig) -> Result<(), DmxError> {
+        let data = config.encode()?;
+        self.check_eeprom_user_size()?;
+
+        match self.backend.eeprom_user_write(&data) {
+            Ok(_) => {}
+            Err(e) => return Err(DmxError::Ftdi("write eeprom", e)),
+        }
+
+        match self.read_eeprom_config() {
+            Ok(Some(stored)) if stored == *config => Ok(()),
+            Ok(_) | Err(DmxError::Decode(_)) => Err(DmxError::ConfigMismatch("eeprom config")),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn check_eeprom_user_size(&mut self) -> Result<(), DmxError> {
+        match self.backend.eeprom_user_size() {
+            Ok(size) if size >= CONFIG_LEN => Ok(()),
+            Ok(_) => Err(DmxError::Ftdi(
+                "find eeprom user area",
+                libftd2xx::FtStatus::EEPROM_NOT_PRESENT,
+            )),
+            Err(e) => Err(DmxError::Ftdi("find eeprom user area", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockBackend;
+
+    fn config() -> EepromConfig {
+        EepromConfig {
+            update_frequency: UpdateFrequency::new(30000).unwrap(),
+            break_time: Duration::from_micros(120),
+            mark_after_break: Duration::from_micros(16),
+        }
+    }
+
+    #[test]
+    fn eeprom_config_test() {
+        let data = config().encode().unwrap();
+        assert_eq!(data.iter().fold(0u8, |sum, v| sum.wrapping_add(*v)), 0);
+        assert_eq!(EepromConfig::decode(&data), Ok(Some(config())));
+        assert_eq!(EepromConfig::decode(&[0; CONFIG_LEN]), Ok(None));
+
+        let mut corrupt = data;
+        corrupt[9] ^= 1;
+        assert_eq!(
+            EepromConfig::decode(&corrupt),
+            Err(DmxError::Decode("eeprom config checksum"))
+        );
+
+        let mut invalid = config();
+        invalid.break_time = Duration::from_micros(50);
+        assert_eq!(invalid.encode(), Err(DmxError::InvalidTiming("break")));
+        assert_eq!(config().timing().break_time, Duration::from_micros(120));
+    }
+
+    #[test]
+    fn mock_eeprom_config_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        assert_eq!(subject.read_eeprom_config(), Ok(None));
+
+        subject.write_eeprom_config(&config()).unwrap();
+        assert_eq!(subject.read_eeprom_config(), Ok(Some(config())));
+
+        // The read back does not match.
+        backend.set_ignore_eeprom_writes(true);
+        let mut other = config();
+        other.update_frequency = UpdateFrequency::DEFAULT;
+        assert_eq!(
+            subject.write_eeprom_config(&other),
+            Err(DmxError::ConfigMismatch("eeprom config"))
+        );
+    }
+}
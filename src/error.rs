@@ -0,0 +1,120 @@
+use libftd2xx::{FtStatus, TimeoutError};
+use std::fmt;
+
+/// Errors reported by an open dmx device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmxError {
+    /// A call to the ftdi driver failed. Contains a short description of the operation
+    /// that failed and the status returned by the driver.
+    Ftdi(&'static str, FtStatus),
+    /// A read or write operation did not transfer all bytes before the time out elapsed.
+    Timeout {
+        operation: &'static str,
+        actual: usize,
+        expected: usize,
+    },
+    /// The given channel does not fit into the dmx buffer.
+    InvalidChannel(usize),
+    /// No device with the given id is connected.
+    DeviceNotFound,
+    /// The device has not been reset yet or has already been closed.
+    NotConnected,
+    /// The background thread controlling the device is not running anymore.
+    Disconnected,
+    /// The USB transfer size is not a multiple of 64 between 64 and 65536 bytes.
+    InvalidTransferSize(u32),
+    /// A setting read back from the device does not match the requested value. Contains the
+    /// name of the setting.
+    ConfigMismatch(&'static str),
+    /// The update frequency is not between UpdateFrequency::MIN and UpdateFrequency::MAX.
+    InvalidUpdateFrequency(u32),
+    /// A fixed frame size is not between 1 and 512 channels.
+    InvalidFrameSize(usize),
+    /// The maximum number of channels is not between DMX_UNIVERSE_SIZE and DMX_MAX_CHANNELS.
+    InvalidMaxChannels(usize),
+    /// A timing setting violates the dmx standard. Contains the name of the setting.
+    InvalidTiming(&'static str),
+    /// No fixture with the given name has been patched.
+    UnknownFixture,
+    /// The fixture profile has no parameter with the given name.
+    UnknownParameter,
+    /// An RDM request failed. Contains a short description of the operation that failed.
+    Rdm(&'static str),
+    /// An RDM device rejected a request. Contains the reason code sent by the device.
+    RdmNack(u16),
+    /// A call to the midi driver failed. Contains a short description of the operation
+    /// that failed.
+    Midi(&'static str),
+    /// A file operation failed. Contains a short description of the operation that failed.
+    Io(&'static str),
+    /// Packed data could not be decoded. Contains a short description of the problem.
+    Decode(&'static str),
+}
+
+impl DmxError {
+    /// Map the error of a libftd2xx read_all/write_all call to a dmx error.
+    pub(crate) fn from_timeout(operation: &'static str, e: TimeoutError) -> Self {
+        match e {
+            TimeoutError::FtStatus(status) => DmxError::Ftdi(operation, status),
+            TimeoutError::Timeout { actual, expected } => DmxError::Timeout {
+                operation,
+                actual,
+                expected,
+            },
+        }
+    }
+
+    /// Report a device, that is not (or no longer) connected, as DeviceNotFound.
+    pub(crate) fn or_not_found(self) -> Self {
+        match self {
+            DmxError::Ftdi(_, FtStatus::DEVICE_NOT_FOUND) => DmxError::DeviceNotFound,
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for DmxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DmxError::Ftdi(operation, status) => {
+                write!(f, "Could not {}. Error: {}", operation, status)
+            }
+            DmxError::Timeout {
+                operation,
+                actual,
+                expected,
+            } => write!(
+                f,
+                "Could not {}. Time out after {} of {} bytes",
+                operation, actual, expected
+            ),
+            DmxError::InvalidChannel(channel) => write!(f, "Invalid channel number: {}", channel),
+            DmxError::DeviceNotFound => write!(f, "No device with the given id is connected"),
+            DmxError::NotConnected => write!(f, "The device is not ready to transmit"),
+            DmxError::Disconnected => write!(f, "The device thread is not running"),
+            DmxError::InvalidTransferSize(size) => write!(f, "Invalid USB transfer size: {}", size),
+            DmxError::ConfigMismatch(setting) => {
+                write!(f, "The device did not apply the {}", setting)
+            }
+            DmxError::InvalidUpdateFrequency(frequency) => {
+                write!(f, "Invalid update frequency: {}", frequency)
+            }
+            DmxError::InvalidFrameSize(channels) => {
+                write!(f, "Invalid frame size: {} channels", channels)
+            }
+            DmxError::InvalidMaxChannels(channels) => {
+                write!(f, "Invalid maximum number of channels: {}", channels)
+            }
+            DmxError::InvalidTiming(setting) => write!(f, "Invalid timing: {}", setting),
+            DmxError::UnknownFixture => write!(f, "Unknown fixture"),
+            DmxError::UnknownParameter => write!(f, "Unknown fixture parameter"),
+            DmxError::Rdm(operation) => write!(f, "Could not {}", operation),
+            DmxError::RdmNack(reason) => write!(f, "RDM request rejected. Reason: {}", reason),
+            DmxError::Midi(operation) => write!(f, "Could not {}", operation),
+            DmxError::Io(operation) => write!(f, "Could not {}", operation),
+            DmxError::Decode(problem) => write!(f, "Could not decode data: {}", problem),
+        }
+    }
+}
+
+impl std::error::Error for DmxError {}
@@ -0,0 +1,82 @@
+use crate::{DmxError, FrameSize};
+use libftd2xx::DeviceStatus;
+use std::{collections::VecDeque, time::Instant};
+
+/// Number of events the background thread started by OpenDMX::run() keeps. Older events are
+/// dropped.
+pub const EVENT_LOG_SIZE: usize = 100;
+
+/// Significant events of the background thread started by OpenDMX::run() (see
+/// OpenDmxProtocol::GetEventLog).
+///
+/// Only settings, that can be changed while the thread runs, are logged: the frame size and
+/// the start code. Timing and the update frequency are fixed once the thread has started. The
+/// thread has no watchdog of its own, losing all senders is logged as ControlLost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// The device has been opened and configured and the thread starts transmitting.
+    Started,
+    /// The device has been reset by a Reset command.
+    Reset,
+    /// A Reset command failed.
+    ResetFailed(DmxError),
+    /// All command senders have been dropped (see OnControlLoss).
+    ControlLost,
+    /// The device has been unplugged while transmitting. The thread stops.
+    Disconnected,
+    /// The device reported a line error or an overrun.
+    LineError(DeviceStatus),
+    /// The frame intervals became irregular (see OpenDmxProtocol::TimingDegraded).
+    TimingDegraded,
+    Paused,
+    Resumed,
+    Idle,
+    Engaged,
+    /// The number of transmitted channels has changed.
+    FrameSizeChanged(FrameSize),
+    /// The start code of the following frames has changed.
+    StartCodeChanged(u8),
+    /// The frame boundaries have been aligned to a clock (see OpenDmxProtocol::SyncClock).
+    ClockSynced,
+}
+
+/// Keeps the most recent events, the oldest first.
+#[derive(Debug, Default)]
+pub(crate) struct EventLog {
+    events: VecDeque<(Instant, EventKind)>,
+}
+
+impl EventLog {
+    pub(crate) fn record(&mut self, kind: EventKind) {
+        if self.events.len() == EVENT_LOG_SIZE {
+            self.events.pop_front();
+        }
+        self.events.push_back((Instant::now(), kind));
+    }
+
+    pub(crate) fn entries(&self) -> Vec<(Instant, EventKind)> {
+        self.events.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_log_test() {
+        let mut subject = EventLog::default();
+        subject.record(EventKind::Started);
+        for _ in 0..EVENT_LOG_SIZE {
+            subject.record(EventKind::Paused);
+        }
+        subject.record(EventKind::Resumed);
+
+        let entries = subject.entries();
+        assert_eq!(entries.len(), EVENT_LOG_SIZE);
+        // The oldest events are dropped.
+        assert_eq!(entries[0].1, EventKind::Paused);
+        assert_eq!(entries[EVENT_LOG_SIZE - 1].1, EventKind::Resumed);
+        assert!(entries.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{DmxError, DMX_UNIVERSE_SIZE};
+use std::collections::HashMap;
+
+/// A named parameter of a fixture, e.g. "dimmer" or "pan".
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FixtureParameter {
+    pub name: String,
+    /// Offset of the channel from the base address of the fixture, starting at 0.
+    pub offset: usize,
+}
+
+/// Describes the channel layout of a fixture type. With the "serde" feature enabled profiles
+/// can be loaded from JSON (see from_json()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FixtureProfile {
+    pub name: String,
+    pub parameters: Vec<FixtureParameter>,
+}
+
+impl FixtureProfile {
+    /// Parse a profile from JSON, e.g.
+    /// `{"name": "PAR", "parameters": [{"name": "dimmer", "offset": 0}]}`.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Offset of the parameter with the given name, if any.
+    pub fn offset(&self, parameter: &str) -> Option<usize> {
+        self.parameters
+            .iter()
+            .find(|p| p.name == parameter)
+            .map(|p| p.offset)
+    }
+
+    /// Number of channels the fixture occupies. Saturates at usize::MAX for offsets, that do not
+    /// fit into any universe.
+    pub fn footprint(&self) -> usize {
+        self.parameters
+            .iter()
+            .map(|p| p.offset.saturating_add(1))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PatchedFixture {
+    profile: FixtureProfile,
+    base: usize,
+}
+
+/// Assigns fixture instances to base addresses and keeps the values of a universe. Fixtures are
+/// addressed by name, the resulting frame can be sent with OpenDMX::set_frame() or
+/// OpenDmxHandle::send_frame().
+#[derive(Debug, Clone)]
+pub struct Patch {
+    fixtures: HashMap<String, PatchedFixture>,
+    frame: [u8; DMX_UNIVERSE_SIZE],
+}
+
+impl Default for Patch {
+    fn default() -> Self {
+        Patch {
+            fixtures: HashMap::new(),
+            frame: [0; DMX_UNIVERSE_SIZE],
+        }
+    }
+}
+
+impl Patch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Patch a fixture with the given profile at the given base address (1 to 512). Fails if the
+    /// fixture does not fit into the universe. Patching a name again replaces the fixture.
+    pub fn add(
+        &mut self,
+        fixture: &str,
+        profile: FixtureProfile,
+        base: usize,
+    ) -> Result<(), DmxError> {
+        if base == 0 {
+            return Err(DmxError::InvalidChannel(base));
+        }
+
+        match base.checked_add(profile.footprint().max(1) - 1) {
+            Some(last) if last <= DMX_UNIVERSE_SIZE => {}
+            Some(last) => return Err(DmxError::InvalidChannel(last)),
+            None => return Err(DmxError::InvalidChannel(usize::MAX)),
+        }
+
+        self.fixtures
+            .insert(fixture.to_owned(), PatchedFixture { profile, base });
+        Ok(())
+    }
+
+    /// Absolute channel of the given parameter of the given fixture.
+    pub fn channel(&self, fixture: &str, parameter: &str) -> Result<usize, DmxError> {
+        let patched = match self.fixtures.get(fixture) {
+            Some(f) => f,
+            None => return Err(DmxError::UnknownFixture),
+        };
+
+        match patched.profile.offset(parameter) {
+            Some(offset) => Ok(patched.base + offset),
+            None => Err(DmxError::UnknownParameter),
+        }
+    }
+
+    /// Set the given parameter of the given fixture.
+    pub fn set(&mut self, fixture: &str, parameter: &str, value: u8) -> Result<(), DmxError> {
+        let channel = self.channel(fixture, parameter)?;
+        self.frame[channel - 1] = value;
+        Ok(())
+    }
+
+    /// Get the value of the given parameter of the given fixture.
+    pub fn get(&self, fixture: &str, parameter: &str) -> Result<u8, DmxError> {
+        let channel = self.channel(fixture, parameter)?;
+        Ok(self.frame[channel - 1])
+    }
+
+    /// All channels of the universe, starting with channel 1.
+    pub fn frame(&self) -> &[u8; DMX_UNIVERSE_SIZE] {
+        &self.frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn par() -> FixtureProfile {
+        FixtureProfile {
+            name: "PAR".to_owned(),
+            parameters: vec![
+                FixtureParameter {
+                    name: "dimmer".to_owned(),
+                    offset: 0,
+                },
+                FixtureParameter {
+                    name: "red".to_owned(),
+                    offset: 1,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn patch_test() {
+        let mut subject = Patch::new();
+        subject.add("front", par(), 10).unwrap();
+        assert_eq!(
+            subject.add("back", par(), 0),
+            Err(DmxError::InvalidChannel(0))
+        );
+        assert_eq!(
+            subject.add("back", par(), DMX_UNIVERSE_SIZE),
+            Err(DmxError::InvalidChannel(DMX_UNIVERSE_SIZE + 1))
+        );
+
+        // Offsets out of range must not overflow.
+        let mut broken = par();
+        broken.parameters[1].offset = usize::MAX;
+        assert_eq!(broken.footprint(), usize::MAX);
+        assert_eq!(
+            subject.add("back", broken.clone(), 1),
+            Err(DmxError::InvalidChannel(usize::MAX))
+        );
+        broken.parameters[1].offset = usize::MAX - 1;
+        assert_eq!(
+            subject.add("back", broken, 2),
+            Err(DmxError::InvalidChannel(usize::MAX))
+        );
+
+        subject.set("front", "red", 255).unwrap();
+        assert_eq!(subject.channel("front", "red"), Ok(11));
+        assert_eq!(subject.get("front", "red"), Ok(255));
+        assert_eq!(subject.frame()[10], 255);
+
+        assert_eq!(subject.set("back", "red", 1), Err(DmxError::UnknownFixture));
+        assert_eq!(
+            subject.set("front", "pan", 1),
+            Err(DmxError::UnknownParameter)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn fixture_profile_json_test() {
+        let json = r#"{"name": "PAR", "parameters": [
+            {"name": "dimmer", "offset": 0},
+            {"name": "red", "offset": 1}
+        ]}"#;
+        assert_eq!(FixtureProfile::from_json(json).unwrap(), par());
+        assert!(FixtureProfile::from_json("{}").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{DmxError, DMX_SLOT_COUNT, DMX_UNIVERSE_SIZE};
+
+/// Number of channels transmitted with every frame. Receivers have to accept frames with less
+/// than 512 channels, but some older ones only work with full frames. Shorter frames can be
+/// transmitted at a higher rate (see UpdateFrequency).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FrameSize {
+    /// Always transmit all 512 channels.
+    #[default]
+    Full512,
+    /// Transmit up to the highest channel, that is not zero (see OpenDMX::active_summary()),
+    /// but at least one channel.
+    Minimal,
+    /// Always transmit the given number of channels, between 1 and 512.
+    Fixed(usize),
+}
+
+impl FrameSize {
+    /// Fails if a fixed size is not between 1 and 512 channels.
+    pub(crate) fn validate(&self) -> Result<(), DmxError> {
+        match self {
+            FrameSize::Fixed(channels) if !(1..=DMX_UNIVERSE_SIZE).contains(channels) => {
+                Err(DmxError::InvalidFrameSize(*channels))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Number of slots of the given frame, that are transmitted, including the start code.
+    pub(crate) fn slots(&self, frame: &[u8; DMX_SLOT_COUNT]) -> usize {
+        let channels = match self {
+            FrameSize::Full512 => DMX_UNIVERSE_SIZE,
+            FrameSize::Minimal => frame[1..]
+                .iter()
+                .rposition(|v| *v != 0)
+                .map_or(1, |index| index + 1),
+            FrameSize::Fixed(channels) => (*channels).min(DMX_UNIVERSE_SIZE),
+        };
+        channels + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_size_test() {
+        let mut frame = [0; DMX_SLOT_COUNT];
+        assert_eq!(FrameSize::Full512.slots(&frame), DMX_SLOT_COUNT);
+        assert_eq!(FrameSize::Minimal.slots(&frame), 2);
+        assert_eq!(FrameSize::Fixed(24).slots(&frame), 25);
+
+        frame[30] = 1;
+        assert_eq!(FrameSize::Minimal.slots(&frame), 31);
+
+        assert_eq!(FrameSize::Fixed(512).validate(), Ok(()));
+        assert_eq!(
+            FrameSize::Fixed(0).validate(),
+            Err(DmxError::InvalidFrameSize(0))
+        );
+        assert_eq!(
+            FrameSize::Fixed(513).validate(),
+            Err(DmxError::InvalidFrameSize(513))
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use crate::DmxError;
+
+/// Update frequency of a device in frames per 1000 seconds, e.g. 40000 for 40 frames per
+/// second. A full dmx frame takes roughly 23ms, so at most 44 frames per second are possible.
+/// Deserialized values are validated like new() does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u32", into = "u32"))]
+pub struct UpdateFrequency(u32);
+
+impl UpdateFrequency {
+    /// One frame per second.
+    pub const MIN: UpdateFrequency = UpdateFrequency(1000);
+    /// Highest update frequency, that makes sense for full dmx frames.
+    pub const MAX: UpdateFrequency = UpdateFrequency(44000);
+    /// 40 frames per second.
+    pub const DEFAULT: UpdateFrequency = UpdateFrequency(40000);
+
+    /// Fails if the frequency is not between MIN and MAX.
+    pub fn new(frequency: u32) -> Result<Self, DmxError> {
+        if !(Self::MIN.0..=Self::MAX.0).contains(&frequency) {
+            return Err(DmxError::InvalidUpdateFrequency(frequency));
+        }
+        Ok(UpdateFrequency(frequency))
+    }
+
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+
+    /// Number of full frames per second.
+    pub fn frames_per_second(&self) -> u32 {
+        self.0 / 1000
+    }
+}
+
+impl TryFrom<u32> for UpdateFrequency {
+    type Error = DmxError;
+
+    fn try_from(frequency: u32) -> Result<Self, Self::Error> {
+        Self::new(frequency)
+    }
+}
+
+impl From<UpdateFrequency> for u32 {
+    fn from(frequency: UpdateFrequency) -> Self {
+        frequency.0
+    }
+}
+
+impl Default for UpdateFrequency {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_frequency_test() {
+        assert_eq!(UpdateFrequency::new(40000), Ok(UpdateFrequency::DEFAULT));
+        assert_eq!(UpdateFrequency::DEFAULT.frames_per_second(), 40);
+        assert_eq!(
+            UpdateFrequency::new(0),
+            Err(DmxError::InvalidUpdateFrequency(0))
+        );
+        assert_eq!(
+            UpdateFrequency::new(44001),
+            Err(DmxError::InvalidUpdateFrequency(44001))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn update_frequency_json_test() {
+        let json = serde_json::to_string(&UpdateFrequency::DEFAULT).unwrap();
+        assert_eq!(json, "40000");
+        assert_eq!(
+            serde_json::from_str::<UpdateFrequency>(&json).unwrap(),
+            UpdateFrequency::DEFAULT
+        );
+        assert!(serde_json::from_str::<UpdateFrequency>("0").is_err());
+        assert!(serde_json::from_str::<UpdateFrequency>("44001").is_err());
+    }
+}
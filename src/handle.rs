@@ -0,0 +1,208 @@
+use crate::{
+    CueList, DmxError, FrameSize, MergeMode, OpenDmxProtocol, TestPattern, DMX_UNIVERSE_SIZE,
+};
+use std::{sync::mpsc::Sender, time::Instant};
+
+/// Controls a device running in a background thread (see OpenDMX::run()). This is a thin
+/// wrapper around the command sender, it can be cloned and shared between threads.
+#[derive(Debug, Clone)]
+pub struct OpenDmxHandle {
+    sender: Sender<OpenDmxProtocol>,
+}
+
+impl From<Sender<OpenDmxProtocol>> for OpenDmxHandle {
+    fn from(sender: Sender<OpenDmxProtocol>) -> Self {
+        OpenDmxHandle { sender }
+    }
+}
+
+impl OpenDmxHandle {
+    pub fn new(sender: Sender<OpenDmxProtocol>) -> Self {
+        OpenDmxHandle { sender }
+    }
+
+    /// Send a command to the background thread. Fails if the thread is not running anymore.
+    pub fn send(&self, cmd: OpenDmxProtocol) -> Result<(), DmxError> {
+        match self.sender.send(cmd) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(DmxError::Disconnected),
+        }
+    }
+
+    /// Set channel x to value y.
+    pub fn set(&self, channel: usize, value: u8) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::SetValue(channel, value))
+    }
+
+    /// Add the given delta to channel x, saturating at 0 and 255.
+    pub fn bump(&self, channel: usize, delta: i16) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::Bump(channel, delta))
+    }
+
+    /// Replace all channels with the given frame.
+    pub fn send_frame(&self, frame: [u8; DMX_UNIVERSE_SIZE]) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::SendFrame(Box::new(frame)))
+    }
+
+    /// Apply the given (channel, value) pairs (see OpenDMX::apply_delta()).
+    pub fn apply_delta(&self, delta: Vec<(usize, u8)>) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::Delta(delta))
+    }
+
+    /// Set all channels to zero or the idle state (see OpenDMX::set_reset_mode()).
+    pub fn blackout(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::ResetBuffer)
+    }
+
+    /// Release the rig to its safe state (see OpenDmxProtocol::ResetToIdle).
+    pub fn reset_to_idle(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::ResetToIdle)
+    }
+
+    /// Set all channels to the given value.
+    pub fn set_all(&self, value: u8) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::SetAll(value))
+    }
+
+    /// Set the number of channels transmitted with every frame (see OpenDMX::set_frame_size()).
+    pub fn set_frame_size(&self, frame_size: FrameSize) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::FrameSize(frame_size))
+    }
+
+    /// Set channel x of the given layer to value y (see OpenDMX::set_layer_value()).
+    pub fn set_layer_value(&self, layer: usize, channel: usize, value: u8) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::SetLayerValue(layer, channel, value))
+    }
+
+    /// Set how the given layer is merged (see OpenDMX::set_layer_mode()).
+    pub fn set_layer_mode(&self, layer: usize, mode: MergeMode) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::SetLayerMode(layer, mode))
+    }
+
+    /// Remove the given layer (see OpenDMX::clear_layer()).
+    pub fn clear_layer(&self, layer: usize) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::ClearLayer(layer))
+    }
+
+    /// Play the given cues (see OpenDmxProtocol::PlayCues).
+    pub fn play_cues(&self, cues: CueList) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::PlayCues(cues))
+    }
+
+    /// Stop the cue list and keep the current values.
+    pub fn stop_cues(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::StopCues)
+    }
+
+    /// Align the frame boundaries to the given clock (see OpenDmxProtocol::SyncClock).
+    pub fn sync_clock(&self, epoch: Instant) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::SyncClock(epoch))
+    }
+
+    /// Replace all channels with the given frame at the given point in time (see
+    /// OpenDmxProtocol::ScheduleFrame).
+    pub fn schedule_frame(
+        &self,
+        at: Instant,
+        frame: &[u8; DMX_UNIVERSE_SIZE],
+    ) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::ScheduleFrame(at, Box::new(*frame)))
+    }
+
+    /// End the fade of the current cue at once (see OpenDmxProtocol::SnapToTarget).
+    pub fn snap_to_target(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::SnapToTarget)
+    }
+
+    /// Transmit the given test pattern (see OpenDmxProtocol::TestPattern).
+    pub fn test_pattern(&self, pattern: TestPattern) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::TestPattern(pattern))
+    }
+
+    /// Stop the test pattern and restore the previous values.
+    pub fn stop_test_pattern(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::StopTestPattern)
+    }
+
+    /// Reset the device.
+    pub fn reset(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::Reset)
+    }
+
+    /// Hold the current frame (see OpenDmxProtocol::Pause).
+    pub fn pause(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::Pause)
+    }
+
+    /// Resume after pause().
+    pub fn resume(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::Resume)
+    }
+
+    /// Stop transmitting, but keep the device open (see OpenDmxProtocol::Idle).
+    pub fn idle(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::Idle)
+    }
+
+    /// Start transmitting again after idle().
+    pub fn engage(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::Engage)
+    }
+
+    /// Request the recently transmitted frames. The frames are returned through the receiver of
+    /// the background thread (see OpenDmxBuilder::recent_frames()).
+    pub fn get_recent_frames(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::GetRecentFrames)
+    }
+
+    /// Request the frame counters. The counters are returned through the receiver of the
+    /// background thread.
+    pub fn get_stats(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::GetStats)
+    }
+
+    /// Request the most recent events of the thread (see OpenDmxProtocol::GetEventLog).
+    pub fn get_event_log(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::GetEventLog)
+    }
+
+    /// Request a list of all devices. The list is returned through the receiver of the
+    /// background thread.
+    pub fn list_devices(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::ListDevices)
+    }
+
+    /// Stop the background thread and free the device.
+    pub fn stop(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::Stop)
+    }
+
+    /// Stop the background thread and free the device without sending a blackout frame (see
+    /// OpenDmxProtocol::StopHold).
+    pub fn stop_hold(&self) -> Result<(), DmxError> {
+        self.send(OpenDmxProtocol::StopHold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn handle_test() {
+        let (sender, receiver) = mpsc::channel();
+        let subject = OpenDmxHandle::from(sender);
+
+        subject.set(1, 255).unwrap();
+        match receiver.try_recv() {
+            Ok(OpenDmxProtocol::SetValue(1, 255)) => {}
+            cmd => panic!("Unexpected command {:?}", cmd),
+        }
+
+        // Once the background thread is gone, sending fails.
+        drop(receiver);
+        assert_eq!(subject.stop(), Err(DmxError::Disconnected));
+    }
+}
@@ -0,0 +1,95 @@
+use std::{collections::VecDeque, time::Duration};
+
+/// Number of frame intervals the jitter is measured over.
+const JITTER_WINDOW: usize = 200;
+
+/// Share of the target interval, the standard deviation may reach before the timing is
+/// considered degraded.
+const JITTER_THRESHOLD: f64 = 0.2;
+
+/// Statistics of the recent frame intervals of the background thread started by
+/// OpenDMX::run() (see OpenDmxProtocol::TimingDegraded).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingStats {
+    /// The interval between two frames, the thread tries to hold.
+    pub target: Duration,
+    pub mean: Duration,
+    pub std_dev: Duration,
+    /// Number of intervals the statistics are based on.
+    pub samples: usize,
+}
+
+impl TimingStats {
+    /// Whether the standard deviation exceeds 20% of the target interval.
+    pub fn is_degraded(&self) -> bool {
+        self.std_dev.as_secs_f64() > self.target.as_secs_f64() * JITTER_THRESHOLD
+    }
+}
+
+/// Keeps the most recent frame intervals.
+#[derive(Debug, Default)]
+pub(crate) struct JitterMeter {
+    intervals: VecDeque<Duration>,
+}
+
+impl JitterMeter {
+    pub(crate) fn record(&mut self, interval: Duration) {
+        if self.intervals.len() == JITTER_WINDOW {
+            self.intervals.pop_front();
+        }
+        self.intervals.push_back(interval);
+    }
+
+    /// Statistics of the recent intervals. None until the window is filled, so a few slow
+    /// frames at start up do not count.
+    pub(crate) fn stats(&self, target: Duration) -> Option<TimingStats> {
+        if self.intervals.len() < JITTER_WINDOW {
+            return None;
+        }
+
+        let samples = self.intervals.len();
+        let mean = self.intervals.iter().map(|i| i.as_secs_f64()).sum::<f64>() / samples as f64;
+        let variance = self
+            .intervals
+            .iter()
+            .map(|i| (i.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / samples as f64;
+
+        Some(TimingStats {
+            target,
+            mean: Duration::from_secs_f64(mean),
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+            samples,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_meter_test() {
+        let target = Duration::from_millis(25);
+        let mut subject = JitterMeter::default();
+        for _ in 0..JITTER_WINDOW - 1 {
+            subject.record(target);
+        }
+        assert!(subject.stats(target).is_none());
+
+        subject.record(target);
+        let stats = subject.stats(target).unwrap();
+        assert_eq!(stats.samples, JITTER_WINDOW);
+        assert_eq!(stats.mean, target);
+        assert!(!stats.is_degraded());
+
+        // Alternating 15ms and 35ms intervals deviate by 10ms from the mean.
+        for i in 0..JITTER_WINDOW {
+            subject.record(Duration::from_millis(if i % 2 == 0 { 15 } else { 35 }));
+        }
+        let stats = subject.stats(target).unwrap();
+        assert_eq!(stats.samples, JITTER_WINDOW);
+        assert!(stats.is_degraded());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{ChannelMask, DmxError, DMX_SLOT_COUNT};
+use std::collections::BTreeMap;
+
+/// How the values of a layer are combined with the layers below.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Highest takes precedence. The higher value wins.
+    #[default]
+    Htp,
+    /// Latest takes precedence. The layer overrides the layers below.
+    Ltp,
+}
+
+#[derive(Debug, Clone)]
+struct Layer {
+    mode: MergeMode,
+    values: [u8; DMX_SLOT_COUNT],
+    /// Channels, that have been set on this layer.
+    channels: ChannelMask,
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Layer {
+            mode: MergeMode::Htp,
+            values: [0; DMX_SLOT_COUNT],
+            channels: ChannelMask::empty(),
+        }
+    }
+}
+
+/// Input layers of a device, e.g. a fader layer and a chase. The layers are merged on top of the
+/// buffer of the device in ascending order of their ids, so with LTP the layer with the
+/// highest id wins.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Layers {
+    layers: BTreeMap<usize, Layer>,
+}
+
+impl Layers {
+    pub(crate) fn set_value(
+        &mut self,
+        layer: usize,
+        channel: usize,
+        value: u8,
+    ) -> Result<(), DmxError> {
+        let layer = self.layers.entry(layer).or_default();
+        layer.channels.insert(channel)?;
+        layer.values[channel] = value;
+
+        Ok(())
+    }
+
+    pub(crate) fn set_mode(&mut self, layer: usize, mode: MergeMode) {
+        self.layers.entry(layer).or_default().mode = mode;
+    }
+
+    pub(crate) fn clear(&mut self, layer: usize) {
+        self.layers.remove(&layer);
+    }
+
+    /// Merge all layers into the given frame.
+    pub(crate) fn merge_into(&self, frame: &mut [u8; DMX_SLOT_COUNT]) {
+        for layer in self.layers.values() {
+            for (channel, value) in frame.iter_mut().enumerate() {
+                if !layer.channels.contains(channel) {
+                    continue;
+                }
+
+                *value = match layer.mode {
+                    MergeMode::Htp => (*value).max(layer.values[channel]),
+                    MergeMode::Ltp => layer.values[channel],
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_test() {
+        let mut subject = Layers::default();
+        subject.set_value(0, 1, 100).unwrap();
+        subject.set_value(0, 2, 100).unwrap();
+        subject.set_value(1, 1, 50).unwrap();
+        assert_eq!(subject.set_value(1, 0, 1), Err(DmxError::InvalidChannel(0)));
+
+        let mut frame = [0; DMX_SLOT_COUNT];
+        frame[3] = 10;
+        subject.merge_into(&mut frame);
+        assert_eq!(&frame[..4], &[0, 100, 100, 10]);
+
+        subject.set_mode(1, MergeMode::Ltp);
+        subject.merge_into(&mut frame);
+        assert_eq!(&frame[..4], &[0, 50, 100, 10]);
+
+        subject.clear(0);
+        subject.clear(1);
+        let mut frame = [0; DMX_SLOT_COUNT];
+        subject.merge_into(&mut frame);
+        assert_eq!(frame[1], 0);
+    }
+}
@@ -1,14 +1,172 @@
-use libftd2xx::{list_devices, num_devices, DeviceInfo, DeviceStatus, Ftdi, FtdiCommon, StopBits};
+use libftd2xx::{
+    library_version, list_devices, num_devices, DeviceInfo, DeviceStatus, Ftdi, Version,
+};
 use std::{
-    sync::mpsc::{self, Receiver, Sender},
-    thread,
+    collections::VecDeque,
+    fmt,
+    sync::{
+        mpsc::{self, Receiver, Sender, SyncSender, TryRecvError},
+        Arc, RwLock,
+    },
+    thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
 
-const BUFFER_SIZE: usize = 513;
+mod backend;
+mod builder;
+mod calibration;
+mod channel_profile;
+mod command;
+mod control_loss;
+mod cue;
+mod curve;
+mod device_info;
+#[cfg(feature = "eeprom")]
+mod eeprom;
+mod error;
+mod event_log;
+mod fixture;
+mod frame_size;
+mod frequency;
+mod handle;
+mod jitter;
+mod layer;
+mod line_check;
+mod manager;
+mod mask;
+#[cfg(feature = "midi")]
+mod midi;
+#[cfg(any(test, feature = "mock"))]
+mod mock;
+mod profile;
+#[cfg(feature = "rdm")]
+mod rdm;
+mod refresh;
+mod reset_mode;
+mod shared;
+mod snapshot;
+mod stats;
+mod test_pattern;
+#[cfg(feature = "virtual")]
+mod timeline;
+mod timing;
+mod transaction;
+mod universe;
+#[cfg(feature = "virtual")]
+mod virtual_dmx;
+
+pub use backend::DmxBackend;
+pub use builder::{
+    FlowControl, OpenDmxBuilder, RtsState, DEFAULT_LATENCY_TIMER, DEFAULT_RESET_ATTEMPTS,
+    DEFAULT_SETTLE_DELAY, DEFAULT_USB_TRANSFER_SIZE,
+};
+pub use calibration::CalibrationReport;
+pub use channel_profile::{ChannelKind, ChannelProfile};
+use command::ResponseSender;
+pub use command::{DmxCommand, DmxEvent};
+pub use control_loss::OnControlLoss;
+use cue::CuePlayer;
+pub use cue::{Cue, CueList};
+pub use curve::IntensityCurve;
+pub use device_info::{DeviceEntry, OpenDmxDeviceInfo};
+#[cfg(feature = "eeprom")]
+pub use eeprom::EepromConfig;
+pub use error::DmxError;
+use event_log::EventLog;
+pub use event_log::{EventKind, EVENT_LOG_SIZE};
+pub use fixture::{FixtureParameter, FixtureProfile, Patch};
+pub use frame_size::FrameSize;
+pub use frequency::UpdateFrequency;
+pub use handle::OpenDmxHandle;
+use jitter::JitterMeter;
+pub use jitter::TimingStats;
+use layer::Layers;
+pub use layer::MergeMode;
+pub use line_check::LineQuality;
+pub use manager::{OpenDmxManager, RunningDevice};
+pub use mask::ChannelMask;
+#[cfg(feature = "midi")]
+pub use midi::{MidiBridge, MidiMapping};
+#[cfg(any(test, feature = "mock"))]
+pub use mock::{MockBackend, MockLineEvent};
+pub use profile::DeviceProfile;
+#[cfg(feature = "rdm")]
+pub use rdm::{RdmTurnaround, Uid, PID_DEVICE_INFO, PID_DMX_START_ADDRESS};
+pub use refresh::RefreshMode;
+pub use reset_mode::ResetMode;
+pub use shared::{ControlMsg, SharedUniverse};
+pub use snapshot::{SharedSnapshot, SnapshotMerge, UniverseSnapshot};
+pub use stats::FrameStats;
+pub use test_pattern::TestPattern;
+#[cfg(feature = "virtual")]
+pub use timeline::{Timeline, TimelineRecorder};
+pub use timing::Timing;
+pub use transaction::ChannelTransaction;
+pub use universe::Universe;
+#[cfg(feature = "virtual")]
+pub use virtual_dmx::VirtualDmx;
+
+/// Number of channels in a dmx universe. Channels are addressed from 1 to 512.
+///
+/// Standard dmx never carries more than 512 channels. Setups with more channels should use one
+/// device per universe (see OpenDmxManager).
+pub const DMX_UNIVERSE_SIZE: usize = 512;
+/// Number of slots in a dmx frame: the start code at slot 0 followed by the 512 channels.
+pub const DMX_SLOT_COUNT: usize = DMX_UNIVERSE_SIZE + 1;
+/// Highest number of channels the buffer of a device can be configured for (see
+/// OpenDmxBuilder::max_channels()).
+pub const DMX_MAX_CHANNELS: usize = 8 * DMX_UNIVERSE_SIZE;
+
 const DMX_BREAK: u64 = 110;
 const DMX_MAB: u64 = 16;
 
+/// Shortest break allowed by the dmx standard in microseconds. Fixtures miss frames with a
+/// shorter break.
+const DMX_MIN_BREAK: u64 = 88;
+/// Shortest mark after break allowed by the dmx standard in microseconds.
+const DMX_MIN_MAB: u64 = 8;
+
+const _: () = assert!(DMX_BREAK >= DMX_MIN_BREAK && DMX_MAB >= DMX_MIN_MAB);
+
+/// Start code of regular dmx frames carrying dimmer data.
+pub const NULL_START_CODE: u8 = 0x00;
+/// Start code of text packets.
+pub const TEXT_START_CODE: u8 = 0x17;
+/// Start code of RDM packets.
+pub const RDM_START_CODE: u8 = 0xCC;
+/// Start code of test packets (see OpenDMX::line_check()).
+pub const TEST_START_CODE: u8 = 0x55;
+
+/// Maximum number of commands the run() thread handles between two frames. Remaining commands
+/// stay queued for the following frames, so a flood of commands cannot stall the output. The
+/// queue of run() is unbounded, use run_bounded() to limit it.
+const MAX_COMMANDS_PER_FRAME: usize = 1024;
+
+/// The run() thread checks the device status every STATUS_POLL_FRAMES frames for line errors.
+const STATUS_POLL_FRAMES: u64 = 40;
+
+/// Time drop waits for the transmit queue to drain before closing the device. A full frame takes
+/// about 23ms on the line.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Bit of the ftdi event status, that is set on a line status change (e.g. framing errors or
+/// overruns).
+const LINE_STATUS_EVENT: u32 = 0x04;
+
+/// Called by the run() thread after every frame, that has been written successfully. Receives
+/// the transmitted channels, where frame[0] is channel 1 (see OpenDMX::set_on_frame()).
+pub type FrameCallback = Box<dyn FnMut(&[u8; DMX_UNIVERSE_SIZE]) + Send>;
+
+/// Called by the run() thread with the new value, whenever the buffered value of a channel has
+/// changed between two frames (see OpenDMX::register_observer()).
+pub type ChannelObserver = Box<dyn FnMut(u8) + Send>;
+
+/// Delay before the first retry of a flaky call in reset(). Doubles with every attempt.
+const RESET_BACKOFF: Duration = Duration::from_millis(5);
+
+/// Number of frames send_once() transmits. Fixtures usually need a few frames to latch new values.
+pub const SEND_ONCE_FRAMES: usize = 5;
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TimerGranularity {
     #[default]
@@ -18,25 +176,180 @@ pub enum TimerGranularity {
 }
 
 /// Commands that are being send to or from the dmx device across multiple threads.
+///
+/// All payloads are Send and Sync, so commands can be sent from any thread. Large and rare
+/// payloads are boxed to keep the enum at 32 bytes, so high rates of SetValue commands move
+/// little data through the channel.
 #[derive(Debug)]
 pub enum OpenDmxProtocol {
-    /// Send to the device. Changes the channel x to value y
+    /// Send to the device. Changes the channel x to value y.
+    /// Values are buffered until the next frame, so if a channel is set several times between
+    /// two frames only the last value is transmitted.
     SetValue(usize, u8),
-    /// Send to device. Stop the thread. This will free the device as well.
+    /// Send to device. Add the given delta to channel x, saturating at 0 and 255 (see
+    /// OpenDMX::bump_dmx_value()). Several changes between two frames add up.
+    Bump(usize, i16),
+    /// Send to device. Replace all channels with the given frame. The start code is preserved.
+    SendFrame(Box<[u8; DMX_UNIVERSE_SIZE]>),
+    /// Send to device. Set channel x of layer l to value y (see OpenDMX::set_layer_value()).
+    SetLayerValue(usize, usize, u8),
+    /// Send to device. Set the merge mode of a layer (see OpenDMX::set_layer_mode()).
+    SetLayerMode(usize, MergeMode),
+    /// Send to device. Remove a layer (see OpenDMX::clear_layer()).
+    ClearLayer(usize),
+    /// Send to device. Apply the given (channel, value) pairs (see OpenDMX::apply_delta()).
+    Delta(Vec<(usize, u8)>),
+    /// Send to device. Stop the thread. This will free the device as well. A blackout frame is
+    /// sent before the device is closed.
     Stop,
+    /// Send to device. Stop the thread and free the device without the blackout frame. Most
+    /// fixtures hold their last values, once the dmx signal stops, so the current look stays
+    /// up. Some fixtures black out or move to a preset instead after a while.
+    StopHold,
     /// Send to device. Reset the device.
     Reset,
-    /// Send to device. Set the entire buffer to zero
+    /// Send to device. Set all channels to zero or the idle state (see
+    /// OpenDMX::set_reset_mode()). The start code is preserved.
     ResetBuffer,
+    /// Send to device. Release the rig to its safe state: stop the cue list and the test
+    /// pattern, remove all layers and set the channels to the idle state (see
+    /// OpenDMX::set_idle_state()).
+    ResetToIdle,
+    /// Send to device. Set all channels to the given value. The start code is preserved.
+    SetAll(u8),
+    /// Send to device. Set the start code of the following frames.
+    StartCode(u8),
+    /// Send to device. Set the number of channels of the following frames. Invalid sizes are
+    /// ignored (see OpenDMX::set_frame_size()).
+    FrameSize(FrameSize),
+    /// Send to device. Set len channels beginning with channel x to zero.
+    ResetRange(usize, usize),
+    /// Send to device. Purge the receive and transmit queues of the device.
+    Purge,
+    /// Send to device. Hold the current frame. The device keeps transmitting it, but ignores
+    /// all commands, that change the buffer, until Resume is send.
+    Pause,
+    /// Send to device. Handle commands, that change the buffer, again.
+    Resume,
+    /// Send to device. Stop transmitting, but keep the device open and handle commands as
+    /// usual, so Engage takes effect with the next frame. No frames, not even breaks, are sent
+    /// until Engage is send. Most fixtures hold their last values meanwhile. Unlike Pause, which
+    /// keeps transmitting the frame.
+    Idle,
+    /// Send to device. Start transmitting again after Idle.
+    Engage,
+    /// Send to device. Request the current position within the frame cycle.
+    GetPhase,
+    /// Send to device. Request the most recently transmitted frames (see
+    /// OpenDmxBuilder::recent_frames()).
+    GetRecentFrames,
+    /// Returned from device. The most recently transmitted frames, the oldest first. frame[0] is
+    /// channel 1.
+    RecentFrames(Vec<[u8; DMX_UNIVERSE_SIZE]>),
+    /// Send to device. Request the frame counters of the thread.
+    GetStats,
+    /// Returned from device. The frame counters of the thread.
+    Stats(Box<FrameStats>),
+    /// Send to device. Request the most recent events of the thread, e.g. for a dashboard
+    /// showing the history of the device.
+    GetEventLog,
+    /// Returned from device. The most recent events of the thread, at most EVENT_LOG_SIZE, the
+    /// oldest first.
+    EventLog(Vec<(Instant, EventKind)>),
     /// Send to device. Lists all available devices.
     ListDevices,
     /// Returned from device. A list of all available devices.
     DeviceList(Vec<DeviceInfo>),
+    /// Returned from device. Send once the device has been opened and starts transmitting.
+    /// Contains the interval between two frames.
+    Started(Duration),
+    /// Returned from device. The time elapsed since the latest frame started and the interval
+    /// between two frames.
+    Phase { elapsed: Duration, frame: Duration },
+    /// Returned from device. Acknowledges a Pause command.
+    Paused,
+    /// Returned from device. Acknowledges a Resume command.
+    Resumed,
+    /// Send to device. Align the frame boundaries to the given point in time: frames start at
+    /// whole multiples of the frame interval after it. Devices, that share the same clock and
+    /// update frequency, transmit their frames in lockstep (see OpenDmxManager).
+    SyncClock(Instant),
+    /// Send to device. Replace all channels with the given frame, where frame[0] is channel 1,
+    /// with the first frame at or after the given point in time. With a shared clock (see
+    /// SyncClock) all devices apply frames scheduled for the same time with the same frame.
+    ScheduleFrame(Instant, Box<[u8; DMX_UNIVERSE_SIZE]>),
+    /// Send to device. Play the given cues one after another. The cue list overwrites the buffer
+    /// with every frame, until the last cue has been held or StopCues is send.
+    PlayCues(CueList),
+    /// Send to device. Stop the cue list. The buffer keeps the current values.
+    StopCues,
+    /// Send to device. End the fade of the current cue at once and jump to its values. The hold
+    /// time of the cue starts now. Does nothing, if no cue is fading.
+    SnapToTarget,
+    /// Send to device. Transmit the given test pattern instead of the buffer, e.g. to verify the
+    /// addresses of the fixtures. Stops the cue list. Commands, that change the buffer, have no
+    /// effect until StopTestPattern is send.
+    TestPattern(TestPattern),
+    /// Send to device. Stop the test pattern and restore the values from before the pattern.
+    StopTestPattern,
+    /// Returned from device. A cue of the cue list has been started. Contains its index.
+    CueStarted(usize),
+    /// Returned from device. The device reported a line error or an overrun. This usually
+    /// indicates wiring problems like reflections or a missing terminator.
+    LineError(DeviceStatus),
+    /// Returned from device. The standard deviation of the recent frame intervals exceeds 20% of
+    /// the target interval. Send at most once per thread. Try a lower update frequency or pin
+    /// the thread to a core (see OpenDmxBuilder).
+    TimingDegraded(Box<TimingStats>),
+    /// Returned from device. The device could not be opened or configured, e.g.
+    /// DmxError::DeviceNotFound if no device with the given id is connected, or has been
+    /// unplugged while transmitting. The thread has stopped.
+    Error(Box<DmxError>),
+}
+
+impl OpenDmxProtocol {
+    /// Whether the command changes the values being transmitted.
+    fn changes_buffer(&self) -> bool {
+        matches!(
+            self,
+            OpenDmxProtocol::SetValue(_, _)
+                | OpenDmxProtocol::Bump(_, _)
+                | OpenDmxProtocol::SendFrame(_)
+                | OpenDmxProtocol::Delta(_)
+                | OpenDmxProtocol::SetLayerValue(_, _, _)
+                | OpenDmxProtocol::SetLayerMode(_, _)
+                | OpenDmxProtocol::ClearLayer(_)
+                | OpenDmxProtocol::PlayCues(_)
+                | OpenDmxProtocol::SnapToTarget
+                | OpenDmxProtocol::TestPattern(_)
+                | OpenDmxProtocol::StopTestPattern
+                | OpenDmxProtocol::ResetBuffer
+                | OpenDmxProtocol::ResetToIdle
+                | OpenDmxProtocol::SetAll(_)
+                | OpenDmxProtocol::StartCode(_)
+                | OpenDmxProtocol::ResetRange(_, _)
+        )
+    }
+}
+
+/// Connection state of a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The device has been opened, but not been configured yet (see OpenDMX::reset()).
+    Opened,
+    /// The device has been configured and is ready to transmit.
+    Ready,
+    /// The device has been closed.
+    Closed,
 }
 
 pub struct OpenDMX {
-    ftdi: Ftdi,
-    buffer: [u8; BUFFER_SIZE],
+    backend: Box<dyn DmxBackend>,
+    state: ConnectionState,
+    /// The start code followed by max_channels channels (see OpenDmxBuilder::max_channels()).
+    buffer: Box<[u8]>,
+    /// Whether the buffer has changed since the last write.
+    dirty: bool,
     info: DeviceInfo,
 
     baud_rate: u32,
@@ -50,94 +363,300 @@ pub struct OpenDMX {
     /// Time out for write operations.
     write_time_out: Duration,
 
-    /// Defaults to 40000 however this might cause flickering in some settings so users should be able to adjust this value.
-    update_frequency: u32,
+    /// Latency timer of the ftdi chip (see DEFAULT_LATENCY_TIMER).
+    latency_timer: Duration,
+
+    /// USB transfer size in bytes. None keeps the driver defaults.
+    usb_transfer_size: Option<u32>,
+
+    flow_control: FlowControl,
+    rts: RtsState,
+    /// Time the run() thread waits after opening the device.
+    settle_delay: Duration,
+    /// Length of the break in front of every frame.
+    break_time: Duration,
+    /// Length of the mark after break.
+    mark_after_break: Duration,
+    /// Time the run() thread holds the line idle after each frame.
+    mark_before_break: Duration,
+
+    /// Curve applied to the intensity channels on write.
+    curve: IntensityCurve,
+
+    /// Kind of every channel. The curve is applied to the intensity channels.
+    channel_profile: ChannelProfile,
+
+    /// Duration of the ramp up of the intensity channels, when the run() thread starts.
+    soft_start: Option<Duration>,
+    /// Level of the intensity channels during the soft start. 255 outside of the soft start.
+    ramp_level: u8,
+
+    /// Input layers merged on top of the buffer on write.
+    layers: Layers,
+
+    /// Driver version read when the device was opened, for the Debug summary.
+    driver_version: Option<Version>,
+
+    /// Library version read when the device was opened, for the Debug summary.
+    library_version: Option<String>,
+
+    /// Defaults to UpdateFrequency::DEFAULT however this might cause flickering in some settings so users can adjust this value (see OpenDmxBuilder::update_frequency()).
+    update_frequency: UpdateFrequency,
+
+    /// Number of channels transmitted with every frame.
+    frame_size: FrameSize,
+
+    /// Called by the run() thread after every frame.
+    on_frame: Option<FrameCallback>,
+
+    /// Called by the run() thread, when the value of their channel changes. Each observer keeps
+    /// the value it has seen last.
+    observers: Vec<(usize, u8, ChannelObserver)>,
+
+    /// Number of transmitted frames the run() thread keeps for debugging.
+    recent_frames: usize,
+
+    /// Number of frames the run() thread transmits a changed buffer before the next change.
+    latch_frames: usize,
+
+    /// How often the run() thread transmits frames.
+    refresh_mode: RefreshMode,
+
+    /// Copied into the buffer by the run_shared() thread before every frame.
+    shared_universe: Option<SharedUniverse>,
+
+    /// What the run() thread does, once all command senders are gone.
+    on_control_loss: OnControlLoss,
+
+    /// Number of attempts for the flaky calls of reset().
+    reset_attempts: u32,
+
+    /// Whether a blackout frame is sent, when the device is dropped.
+    blackout_on_drop: bool,
+
+    /// The values reset_buffer() and the blackout frame on drop set the channels to.
+    reset_mode: ResetMode,
+
+    /// How the line is turned around for RDM responses.
+    #[cfg(feature = "rdm")]
+    rdm_turnaround: RdmTurnaround,
 }
 
 impl OpenDMX {
     /// Create a new device. Creating a device might fail (if no device is connected) this is why we return a result here.
-    pub fn new(device_id: i32) -> Result<Self, String> {
-        let mut ft: Ftdi;
-        match Ftdi::with_index(device_id) {
-            Ok(d) => {
-                ft = d;
-            }
-            Err(e) => {
-                return Err(format!("Could not open ftdi device. Error: {}", e));
-            }
-        }
+    /// Use OpenDMX::builder() to create a device with non default settings.
+    pub fn new(device_id: i32) -> Result<Self, DmxError> {
+        OpenDmxBuilder::new(device_id).build()
+    }
 
-        let device_info: DeviceInfo;
-        match ft.device_info() {
-            Ok(d) => {
-                device_info = d;
-            }
-            Err(e) => {
-                return Err(format!("Could read device info. Error: {}", e));
-            }
+    /// The timing settings of this device (see OpenDmxBuilder::timing()).
+    pub fn timing(&self) -> Timing {
+        Timing {
+            break_time: self.break_time,
+            mark_after_break: self.mark_after_break,
+            mark_before_break: self.mark_before_break,
+            settle_delay: self.settle_delay,
+            latency_timer: self.latency_timer,
+            update_frequency: self.update_frequency,
         }
+    }
+
+    /// The baud rate the device is configured with on reset().
+    pub fn baud_rate(&self) -> u32 {
+        self.baud_rate
+    }
+
+    /// The number of data bits per slot the device is configured with on reset().
+    pub fn bits_per_word(&self) -> libftd2xx::BitsPerWord {
+        self.bits_per_word
+    }
+
+    /// The number of stop bits the device is configured with on reset().
+    pub fn stop_bits(&self) -> libftd2xx::StopBits {
+        self.stop_bits
+    }
+
+    /// The parity the device is configured with on reset().
+    pub fn parity(&self) -> libftd2xx::Parity {
+        self.parity_none
+    }
+
+    /// The underlying ftdi device for operations this crate does not wrap, e.g. EEPROM reads or
+    /// bit bang mode. Returns None for other backends (see new_with_backend()).
+    ///
+    /// This is an escape hatch. Changing the device settings behind the back of this object
+    /// breaks its assumptions about timing and state, call reset() afterwards to restore them.
+    /// A device, that is used by run(), is owned by the background thread and can not be
+    /// reached here.
+    pub fn ftdi_mut(&mut self) -> Option<&mut Ftdi> {
+        self.backend.as_ftdi_mut()
+    }
 
-        Ok(OpenDMX {
-            ftdi: ft,
-            buffer: [0; BUFFER_SIZE],
-            info: device_info,
-            baud_rate: 250000,
-            bits_per_word: libftd2xx::BitsPerWord::Bits8,
-            stop_bits: StopBits::Bits2,
-            read_time_out: Duration::from_millis(500),
-            write_time_out: Duration::from_millis(500),
-            parity_none: libftd2xx::Parity::No,
-            update_frequency: 40000,
-        })
+    /// Same as new() but keeps trying to open the device every poll interval, until it succeeds
+    /// or the timeout elapses (see OpenDmxBuilder::build_with_retry()).
+    pub fn with_index_retry(
+        device_id: i32,
+        timeout: Duration,
+        poll: Duration,
+    ) -> Result<Self, DmxError> {
+        OpenDmxBuilder::new(device_id).build_with_retry(timeout, poll)
+    }
+
+    /// Create a device, that uses the given backend instead of an ftdi device.
+    pub fn new_with_backend<B: DmxBackend + 'static>(backend: B) -> Result<Self, DmxError> {
+        OpenDmxBuilder::new(0).build_with_backend(Box::new(backend))
+    }
+
+    /// Create a configuration for the device with the given id.
+    pub fn builder(device_id: i32) -> OpenDmxBuilder {
+        OpenDmxBuilder::new(device_id)
+    }
+
+    /// Same as reset() but reads back the settings, that the driver can report, and fails if
+    /// one of them has not been applied. Some setters silently do nothing on flaky USB hubs,
+    /// which results in subtly wrong timing. Only the latency timer can be read back, the baud
+    /// rate and the data characteristics can not be verified.
+    pub fn checked_reset(&mut self) -> Result<(), DmxError> {
+        self.reset()?;
+
+        match self.backend.latency_timer() {
+            Ok(t) if t == self.latency_timer => Ok(()),
+            Ok(_) => Err(DmxError::ConfigMismatch("latency timer")),
+            Err(e) => Err(DmxError::Ftdi("read latency timer", e)),
+        }
     }
 
     /// Reset the device.
-    pub fn reset(&mut self) -> Result<(), String> {
-        match self.ftdi.reset() {
-            Ok(_) => {}
-            Err(e) => return Err(format!("Could not reset device. Error: {}", e)),
+    /// Once the device has been reset successfully it is ready to transmit.
+    pub fn reset(&mut self) -> Result<(), DmxError> {
+        if self.state == ConnectionState::Closed {
+            return Err(DmxError::NotConnected);
         }
+        self.state = ConnectionState::Opened;
 
-        match self.ftdi.set_baud_rate(self.baud_rate) {
+        match self.backend.reset() {
             Ok(_) => {}
-            Err(e) => return Err(format!("Could not set baud rate. Error: {}", e)),
-        };
+            Err(e) => return Err(DmxError::Ftdi("reset device", e)),
+        }
+
+        let baud_rate = self.baud_rate;
+        self.retry(|device| match device.backend.set_baud_rate(baud_rate) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DmxError::Ftdi("set baud rate", e)),
+        })?;
 
-        match self.ftdi.set_data_characteristics(
+        match self.backend.set_data_characteristics(
             self.bits_per_word,
             self.stop_bits,
             self.parity_none,
         ) {
             Ok(_) => {}
-            Err(e) => return Err(format!("Could not set data characteristics. Error: {}", e)),
+            Err(e) => return Err(DmxError::Ftdi("set data characteristics", e)),
         };
 
         match self
-            .ftdi
+            .backend
             .set_timeouts(self.read_time_out, self.write_time_out)
         {
             Ok(_) => {}
-            Err(e) => return Err(format!("Could not set time outs. Error: {}", e)),
+            Err(e) => return Err(DmxError::Ftdi("set time outs", e)),
         };
 
-        match self.ftdi.set_flow_control_none() {
+        match self.backend.set_latency_timer(self.latency_timer) {
             Ok(_) => {}
-            Err(e) => return Err(format!("Could not set flow control. Error: {}", e)),
+            Err(e) => return Err(DmxError::Ftdi("set latency timer", e)),
         };
 
-        match self.ftdi.clear_rts() {
+        if let Some(size) = self.usb_transfer_size {
+            match self.backend.set_usb_parameters(size) {
+                Ok(_) => {}
+                Err(e) => return Err(DmxError::Ftdi("set usb parameters", e)),
+            };
+        }
+
+        let flow_control = match self.flow_control {
+            FlowControl::None => self.backend.set_flow_control_none(),
+            FlowControl::RtsCts => self.backend.set_flow_control_rts_cts(),
+            FlowControl::DtrDsr => self.backend.set_flow_control_dtr_dsr(),
+            FlowControl::XonXoff { xon, xoff } => self.backend.set_flow_control_xon_xoff(xon, xoff),
+        };
+        match flow_control {
             Ok(_) => {}
-            Err(e) => return Err(format!("Could not clear rts. Error: {}", e)),
+            Err(e) => return Err(DmxError::Ftdi("set flow control", e)),
         };
 
-        match self.ftdi.purge_rx() {
+        self.apply_rts(self.rts)?;
+
+        self.retry(|device| device.purge())?;
+        self.state = ConnectionState::Ready;
+
+        Ok(())
+    }
+
+    /// Call f until it succeeds or reset_attempts have been made. Returns the last error.
+    fn retry<T>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<T, DmxError>,
+    ) -> Result<T, DmxError> {
+        let mut backoff = RESET_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            match f(self) {
+                Ok(result) => return Ok(result),
+                Err(e) if attempt < self.reset_attempts => {
+                    log::debug!(
+                        "{} (attempt {} of {}). Retrying.",
+                        e,
+                        attempt,
+                        self.reset_attempts
+                    );
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// The connection state of the device.
+    pub fn connection_state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Fail unless the device is ready to transmit.
+    fn ensure_ready(&self) -> Result<(), DmxError> {
+        match self.state {
+            ConnectionState::Ready => Ok(()),
+            ConnectionState::Opened | ConnectionState::Closed => Err(DmxError::NotConnected),
+        }
+    }
+
+    /// Set or clear the RTS line.
+    fn apply_rts(&mut self, rts: RtsState) -> Result<(), DmxError> {
+        match rts {
+            RtsState::Clear => match self.backend.clear_rts() {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DmxError::Ftdi("clear rts", e)),
+            },
+            RtsState::Set => match self.backend.set_rts() {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DmxError::Ftdi("set rts", e)),
+            },
+        }
+    }
+
+    /// Purge the receive and transmit queues of the device. Unlike reset() this does not touch
+    /// the device configuration, so it can be used to get rid of stale data at any time.
+    pub fn purge(&mut self) -> Result<(), DmxError> {
+        match self.backend.purge_rx() {
             Ok(_) => {}
-            Err(e) => return Err(format!("Could not purge (1). Error: {}", e)),
+            Err(e) => return Err(DmxError::Ftdi("purge receive queue", e)),
         };
 
-        match self.ftdi.purge_tx() {
+        match self.backend.purge_tx() {
             Ok(_) => {}
-            Err(e) => return Err(format!("Could not purge (2). Error: {}", e)),
+            Err(e) => return Err(DmxError::Ftdi("purge transmit queue", e)),
         };
 
         Ok(())
@@ -145,370 +664,3221 @@ impl OpenDMX {
 
     /// Set the value of the given channel. The data is not written directly to the device but
     /// buffered until a call to write().
-    pub fn set_dmx_value(&mut self, channel: usize, value: u8) -> Result<(), String> {
-        if channel >= BUFFER_SIZE {
-            return Err("Invalid channel number".to_owned());
+    /// Channels 1 to channel_count() are valid, channel 0 is the start code. This is raw access
+    /// to the slots of the frame, use set_channel() to address channels only.
+    pub fn set_dmx_value(&mut self, channel: usize, value: u8) -> Result<(), DmxError> {
+        if channel >= self.buffer.len() {
+            return Err(DmxError::InvalidChannel(channel));
         }
         self.buffer[channel] = value;
+        self.dirty = true;
+
+        Ok(())
+    }
+
+    /// Best effort version of set_dmx_value() for callers without error handling, e.g. UI
+    /// sliders, that briefly leave the valid range while being dragged. This never fails:
+    /// channels outside of 1 to channel_count() are silently dropped, the start code is never
+    /// changed.
+    pub fn set_dmx_value_clamped(&mut self, channel: usize, value: u8) {
+        if (1..self.buffer.len()).contains(&channel) {
+            self.buffer[channel] = value;
+            self.dirty = true;
+        }
+    }
+
+    /// Set the value of the given dmx address, from 1 to DMX_UNIVERSE_SIZE, like it is printed on
+    /// fixtures. Fails for 0 instead of changing the start code (see set_start_code()), so
+    /// off-by-one mistakes show up as errors. Otherwise the same as set_dmx_value().
+    pub fn set_channel(&mut self, dmx_address: usize, value: u8) -> Result<(), DmxError> {
+        if dmx_address == 0 {
+            return Err(DmxError::InvalidChannel(dmx_address));
+        }
+        self.set_dmx_value(dmx_address, value)
+    }
+
+    /// Same as set_channel() for a channel known at compile time, e.g.
+    /// `device.set_channel_const::<1>(255)`. Channels outside of 1 to DMX_UNIVERSE_SIZE fail to
+    /// compile, so there is no error to handle.
+    pub fn set_channel_const<const C: usize>(&mut self, value: u8) {
+        const { assert!(C >= 1 && C <= DMX_UNIVERSE_SIZE, "Invalid channel number") };
+        self.buffer[C] = value;
+        self.dirty = true;
+    }
+
+    /// Replace all channels with the given frame, where frame[0] is channel 1. The start code is
+    /// preserved.
+    pub fn set_frame(&mut self, frame: &[u8; DMX_UNIVERSE_SIZE]) {
+        self.buffer[1..DMX_SLOT_COUNT].copy_from_slice(frame);
+        self.dirty = true;
+    }
+
+    /// Replace all channels with the given frame, where new_buffer[0] is channel 1, and return
+    /// the previous channels. The start code is preserved. This allows to build the next frame
+    /// in a separate buffer and swap it in without copying single channels.
+    pub fn replace_buffer(
+        &mut self,
+        mut new_buffer: [u8; DMX_UNIVERSE_SIZE],
+    ) -> [u8; DMX_UNIVERSE_SIZE] {
+        self.buffer[1..DMX_SLOT_COUNT].swap_with_slice(&mut new_buffer);
+        self.dirty = true;
+        new_buffer
+    }
+
+    /// Number of channels of the device, not counting the start code. This is DMX_UNIVERSE_SIZE
+    /// unless a higher maximum has been configured (see OpenDmxBuilder::max_channels()).
+    pub fn channel_count(&self) -> usize {
+        self.buffer.len() - 1
+    }
+
+    /// Number of channels with a value other than zero and the highest of these channels, or
+    /// zero if all channels are zero. The start code is skipped.
+    pub fn active_summary(&self) -> (usize, usize) {
+        self.buffer
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, v)| **v != 0)
+            .fold((0, 0), |(count, _), (channel, _)| (count + 1, channel))
+    }
+
+    /// Set the value of the given channel from a normalized value. The value is clamped to
+    /// 0.0..=1.0 and mapped to 0..=255, rounding halves up (NaN maps to 0).
+    pub fn set_dmx_value_f32(&mut self, channel: usize, value: f32) -> Result<(), DmxError> {
+        let value = (value.clamp(0.0, 1.0) * 255.0 + 0.5).floor() as u8;
+        self.set_dmx_value(channel, value)
+    }
+
+    /// Add the given delta to the value of the given channel, saturating at 0 and 255, e.g. for
+    /// encoders or jog wheels sending relative changes. Returns the new value. Otherwise the
+    /// same as set_dmx_value().
+    pub fn bump_dmx_value(&mut self, channel: usize, delta: i16) -> Result<u8, DmxError> {
+        let value = (self.get_dmx_value(channel)? as i16).saturating_add(delta);
+        let value = value.clamp(0, u8::MAX as i16) as u8;
+        self.set_dmx_value(channel, value)?;
+
+        Ok(value)
+    }
+
+    /// Read the value of the given channel as normalized value in 0.0..=1.0.
+    pub fn get_dmx_value_f32(&self, channel: usize) -> Result<f32, DmxError> {
+        Ok(self.get_dmx_value(channel)? as f32 / 255.0)
+    }
+
+    /// Set the value of the given channel from a percentage. The percentage is clamped to
+    /// 0..=100 and mapped to the nearest value in 0..=255, rounding halves up (50 maps to 128).
+    pub fn set_dmx_percent(&mut self, channel: usize, percent: u8) -> Result<(), DmxError> {
+        let value = (percent.min(100) as u16 * 255 + 50) / 100;
+        self.set_dmx_value(channel, value as u8)
+    }
+
+    /// Read the value of the given channel as percentage, rounded to the nearest percent
+    /// (128 maps to 50).
+    pub fn get_dmx_percent(&self, channel: usize) -> Result<u8, DmxError> {
+        let value = self.get_dmx_value(channel)? as u16;
+        Ok(((value * 100 + 127) / 255) as u8)
+    }
+
+    /// Start a transaction to update several channels at once. Either all values of the
+    /// transaction are applied on commit() or none.
+    pub fn transaction(&mut self) -> ChannelTransaction<'_> {
+        ChannelTransaction::new(self)
+    }
+
+    /// Copy the channels of the given snapshot into the buffer. The start code is preserved.
+    pub fn apply_snapshot(&mut self, snapshot: &UniverseSnapshot) {
+        let mut frame = [0; DMX_UNIVERSE_SIZE];
+        frame.copy_from_slice(&snapshot.values()[1..]);
+        self.set_frame(&frame);
+    }
+
+    /// Load a whole scene, e.g. an array literal, where frame[0] is channel 1. Same as
+    /// set_frame() for callers, that hand the frame over. The start code is preserved.
+    pub fn load(&mut self, frame: [u8; DMX_UNIVERSE_SIZE]) {
+        self.set_frame(&frame);
+    }
+
+    /// Copy the given universe into the buffer. The start code is preserved.
+    pub fn output(&mut self, universe: &Universe) {
+        self.set_frame(&universe.snapshot());
+    }
+
+    /// Set the value of the given channel on the given input layer. The layers are merged on top
+    /// of the buffer, when the frame is written (see MergeMode). A layer is created, when it is
+    /// used for the first time. Channels 1 to DMX_UNIVERSE_SIZE are valid.
+    pub fn set_layer_value(
+        &mut self,
+        layer: usize,
+        channel: usize,
+        value: u8,
+    ) -> Result<(), DmxError> {
+        self.layers.set_value(layer, channel, value)?;
+        self.dirty = true;
 
         Ok(())
     }
 
+    /// Set how the given layer is merged. Layers default to HTP.
+    pub fn set_layer_mode(&mut self, layer: usize, mode: MergeMode) {
+        self.layers.set_mode(layer, mode);
+        self.dirty = true;
+    }
+
+    /// Remove the given layer, so it does not contribute to the output anymore.
+    pub fn clear_layer(&mut self, layer: usize) {
+        self.layers.clear(layer);
+        self.dirty = true;
+    }
+
+    /// Apply the given (channel, value) pairs, e.g. the changes received from a remote console
+    /// (see UniverseSnapshot::diff()). Nothing is applied unless all channels are valid.
+    pub fn apply_delta(&mut self, delta: &[(usize, u8)]) -> Result<(), DmxError> {
+        let mut transaction = self.transaction();
+        for (channel, value) in delta {
+            transaction.set(*channel, *value);
+        }
+        transaction.commit()
+    }
+
     /// Read the value for the given channel from the local buffer. This is not the value stored on
     /// the open_dmx device. In order to read values from the device the local buffer and
     /// the device have to be synchronized first (see self.sync()).
-    pub fn get_dmx_value(&self, channel: usize) -> Result<u8, String> {
-        if channel >= BUFFER_SIZE {
-            return Err("Invalid channel number".to_owned());
+    pub fn get_dmx_value(&self, channel: usize) -> Result<u8, DmxError> {
+        if channel >= self.buffer.len() {
+            return Err(DmxError::InvalidChannel(channel));
         }
         Ok(self.buffer[channel])
     }
 
+    /// Same as get_dmx_value() for a channel known at compile time. Channels outside of 1 to
+    /// DMX_UNIVERSE_SIZE fail to compile (see set_channel_const()).
+    pub fn get_channel_const<const C: usize>(&self) -> u8 {
+        const { assert!(C >= 1 && C <= DMX_UNIVERSE_SIZE, "Invalid channel number") };
+        self.buffer[C]
+    }
+
+    /// Whether channels 1 to 512 of the buffer equal the given frame, where frame[0] is channel
+    /// 1. The start code is not compared.
+    pub fn buffer_eq(&self, other: &[u8; DMX_UNIVERSE_SIZE]) -> bool {
+        self.buffer[1..DMX_SLOT_COUNT] == other[..]
+    }
+
+    /// Whether the channels beginning with start equal the given values. Returns false if the
+    /// range does not fit into channels 1 to channel_count().
+    pub fn buffer_matches_range(&self, start: usize, values: &[u8]) -> bool {
+        match self.channel_range(start, values.len()) {
+            Ok(range) => self.buffer[range] == *values,
+            Err(_) => false,
+        }
+    }
+
+    /// Read len channels beginning with start from the local buffer. Fails with the first
+    /// channel, that is out of range (see get_range_into() to avoid the allocation).
+    pub fn get_range(&self, start: usize, len: usize) -> Result<Vec<u8>, DmxError> {
+        let range = self.channel_range(start, len)?;
+        Ok(self.buffer[range].to_vec())
+    }
+
+    /// Copy the channels beginning with start into values. Nothing is copied if the range does
+    /// not fit into channels 1 to channel_count().
+    pub fn get_range_into(&self, start: usize, values: &mut [u8]) -> Result<(), DmxError> {
+        let range = self.channel_range(start, values.len())?;
+        values.copy_from_slice(&self.buffer[range]);
+        Ok(())
+    }
+
+    /// The buffer indices of len channels beginning with start.
+    fn channel_range(&self, start: usize, len: usize) -> Result<std::ops::Range<usize>, DmxError> {
+        if start == 0 {
+            return Err(DmxError::InvalidChannel(start));
+        }
+        match start.checked_add(len) {
+            Some(end) if end <= self.buffer.len() => Ok(start..end),
+            _ => Err(DmxError::InvalidChannel(start.max(self.buffer.len()))),
+        }
+    }
+
     /// Synchornize local buffer with open_dmx device.
-    pub fn sync(&mut self) -> Result<(), String> {
-        let data = self.read().unwrap();
+    ///
+    /// This overwrites the transmit buffer with the received data. A transmit-only device
+    /// usually receives nothing or zeros, which wipes the current values. Use read_into() to
+    /// inspect received data without touching the buffer.
+    pub fn sync(&mut self) -> Result<(), DmxError> {
+        let data = self.read()?;
 
         for (dst, src) in self.buffer.iter_mut().zip(&data) {
             *dst = *src
         }
+        self.dirty = true;
 
         Ok(())
     }
 
+    /// Read the received data into the given buffer without touching the transmit buffer.
+    /// Returns the number of bytes copied, which is limited by the size of dst. Any data, that
+    /// does not fit into dst, is discarded.
+    pub fn read_into(&mut self, dst: &mut [u8]) -> Result<usize, DmxError> {
+        let data = self.receive()?;
+        let len = data.len().min(dst.len());
+        dst[..len].copy_from_slice(&data[..len]);
+
+        Ok(len)
+    }
+
+    /// Wait until len bytes have been received or the timeout has elapsed and return the first
+    /// len bytes of the receive queue, e.g. for a reply of a bidirectional protocol. Fails with
+    /// DmxError::Timeout, if less bytes arrive in time. Nothing is consumed in that case.
+    pub fn read_exact_timeout(
+        &mut self,
+        len: usize,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, DmxError> {
+        self.ensure_ready()?;
+
+        let start = Instant::now();
+        loop {
+            let available = match self.backend.queue_status() {
+                Ok(s) => s,
+                Err(e) => return Err(DmxError::Ftdi("read queue status", e)),
+            };
+            if available >= len {
+                break;
+            }
+            if start.elapsed() >= timeout {
+                return Err(DmxError::Timeout {
+                    operation: "read device data",
+                    actual: available,
+                    expected: len,
+                });
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let mut buf = vec![0; len];
+        match self.backend.read_all(&mut buf) {
+            Ok(_) => Ok(buf),
+            Err(e) => Err(DmxError::from_timeout("read device data", e)),
+        }
+    }
+
     /// Close the current device. This is automatically called when a dmx device is dropped.
-    pub(crate) fn close(&mut self) -> Result<(), String> {
-        match self.ftdi.close() {
+    pub(crate) fn close(&mut self) -> Result<(), DmxError> {
+        if self.state == ConnectionState::Closed {
+            return Ok(());
+        }
+        self.state = ConnectionState::Closed;
+
+        match self.backend.close() {
             Ok(_) => Ok(()),
-            Err(e) => Err(format!("Could close device. Error: {}", e)),
+            Err(e) => Err(DmxError::Ftdi("close device", e)),
         }
     }
 
     /// Read current device status.
-    pub fn read(&mut self) -> Result<Vec<u8>, String> {
-        let size: usize;
-        match self.ftdi.queue_status() {
-            Ok(s) => {
-                size = s;
-            }
+    pub fn read(&mut self) -> Result<Vec<u8>, DmxError> {
+        self.ensure_ready()?;
+
+        let size = match self.backend.queue_status() {
+            Ok(s) => s,
             Err(e) => {
-                return Err(format!("Could read queue status. Error: {}", e));
+                return Err(DmxError::Ftdi("read queue status", e));
             }
-        }
+        };
 
         let mut buf: [u8; 4096] = [0; 4096];
-        match self.ftdi.read_all(&mut buf[0..size]) {
+        match self.backend.read_all(&mut buf[0..size]) {
             Ok(_) => {
                 let r: Vec<u8> = buf.into();
                 Ok(r)
             }
-            Err(e) => Err(format!("Could read device data. Error: {}", e)),
+            Err(e) => Err(DmxError::from_timeout("read device data", e)),
+        }
+    }
+
+    /// Read exactly the bytes waiting in the receive queue.
+    fn receive(&mut self) -> Result<Vec<u8>, DmxError> {
+        self.ensure_ready()?;
+
+        let size = match self.backend.queue_status() {
+            Ok(s) => s,
+            Err(e) => {
+                return Err(DmxError::Ftdi("read queue status", e));
+            }
+        };
+
+        let mut buf = vec![0; size];
+        match self.backend.read_all(&mut buf) {
+            Ok(_) => Ok(buf),
+            Err(e) => Err(DmxError::from_timeout("read device data", e)),
+        }
+    }
+
+    /// Version of the D2XX library, e.g. "1.4.27".
+    pub fn library_version() -> Result<String, DmxError> {
+        match library_version() {
+            Ok(v) => Ok(v.to_string()),
+            Err(e) => Err(DmxError::Ftdi("read library version", e)),
+        }
+    }
+
+    /// Version of the driver of this device, e.g. "2.12.36".
+    pub fn driver_version(&mut self) -> Result<String, DmxError> {
+        match self.backend.driver_version() {
+            Ok(v) => Ok(v.to_string()),
+            Err(e) => Err(DmxError::Ftdi("read driver version", e)),
         }
     }
 
     /// Return the number of devices.
-    pub fn get_num_of_devices() -> Result<u32, String> {
+    pub fn get_num_of_devices() -> Result<u32, DmxError> {
         match num_devices() {
             Ok(num) => Ok(num),
-            Err(e) => Err(format!(
-                "Could not retrieve number of devices. Error: {}",
-                e
-            )),
+            Err(e) => Err(DmxError::Ftdi("retrieve number of devices", e)),
         }
     }
 
-    pub fn list_devices() -> Result<Vec<DeviceInfo>, String> {
+    /// List all ftdi devices connected to this machine.
+    pub fn list_devices() -> Result<Vec<DeviceInfo>, DmxError> {
         match list_devices() {
             Ok(l) => Ok(l),
-            Err(e) => match e {
-                libftd2xx::FtStatus::INVALID_HANDLE => Err("INVALID_HANDLE".to_string()),
-                libftd2xx::FtStatus::DEVICE_NOT_FOUND => Err("DEVICE_NOT_FOUND".to_string()),
-                libftd2xx::FtStatus::DEVICE_NOT_OPENED => Err("DEVICE_NOT_OPENED".to_string()),
-                libftd2xx::FtStatus::IO_ERROR => Err("IO_ERROR".to_string()),
-                libftd2xx::FtStatus::INSUFFICIENT_RESOURCES => {
-                    Err("INSUFFICIENT_RESOURCES".to_string())
-                }
-                libftd2xx::FtStatus::INVALID_PARAMETER => Err("INVALID_PARAMETER".to_string()),
-                libftd2xx::FtStatus::INVALID_BAUD_RATE => Err("INVALID_BAUD_RATE".to_string()),
-                libftd2xx::FtStatus::DEVICE_NOT_OPENED_FOR_ERASE => {
-                    Err("DEVICE_NOT_OPENED_FOR_ERASE".to_string())
-                }
-                libftd2xx::FtStatus::DEVICE_NOT_OPENED_FOR_WRITE => {
-                    Err("DEVICE_NOT_OPENED_FOR_WRITE".to_string())
-                }
-                libftd2xx::FtStatus::FAILED_TO_WRITE_DEVICE => {
-                    Err("FAILED_TO_WRITE_DEVICE".to_string())
-                }
-                libftd2xx::FtStatus::EEPROM_READ_FAILED => Err("EEPROM_READ_FAILED".to_string()),
-                libftd2xx::FtStatus::EEPROM_WRITE_FAILED => Err("EEPROM_WRITE_FAILED".to_string()),
-                libftd2xx::FtStatus::EEPROM_ERASE_FAILED => Err("EEPROM_ERASE_FAILED".to_string()),
-                libftd2xx::FtStatus::EEPROM_NOT_PRESENT => Err("EEPROM_NOT_PRESENT".to_string()),
-                libftd2xx::FtStatus::EEPROM_NOT_PROGRAMMED => {
-                    Err("EEPROM_NOT_PROGRAMMED".to_string())
-                }
-                libftd2xx::FtStatus::INVALID_ARGS => Err("INVALID_ARGS".to_string()),
-                libftd2xx::FtStatus::NOT_SUPPORTED => Err("NOT_SUPPORTED".to_string()),
-                libftd2xx::FtStatus::OTHER_ERROR => Err("OTHER_ERROR".to_string()),
-                libftd2xx::FtStatus::DEVICE_LIST_NOT_READY => {
-                    Err("DEVICE_LIST_NOT_READY".to_string())
-                }
-            },
+            Err(e) => Err(DmxError::Ftdi("list devices", e)),
         }
     }
 
-    /// Retrieve data about the current device.
-    pub fn get_device_info(&self) -> &DeviceInfo {
-        &self.info
+    /// List all connected devices with their device id and whether they are already opened.
+    /// Unlike list_devices() the entries do not tie applications to libftd2xx.
+    pub fn enumerate() -> Result<Vec<DeviceEntry>, DmxError> {
+        Ok(Self::list_devices()?
+            .iter()
+            .enumerate()
+            .map(|(index, info)| DeviceEntry::new(index as i32, info))
+            .collect())
     }
 
-    pub fn set_break(&mut self, on: bool) -> bool {
-        if on {
-            match self.ftdi.set_break_on() {
-                Ok(_) => true,
-                Err(_) => false,
-            }
-        } else {
-            match self.ftdi.set_break_off() {
-                Ok(_) => true,
-                Err(_) => false,
-            }
+    /// Whether the device with the given id is currently opened, by this or another process.
+    /// The device is not opened for the check, so a device picker can gray out busy devices.
+    /// Returns false if the device does not exist or the devices cannot be listed.
+    pub fn is_in_use(device_id: i32) -> bool {
+        let index = match usize::try_from(device_id) {
+            Ok(i) => i,
+            Err(_) => return false,
+        };
+
+        match Self::list_devices() {
+            Ok(devices) => devices.get(index).is_some_and(|d| d.port_open),
+            Err(_) => false,
         }
     }
 
-    /// Get device status from the current device.
-    pub fn get_device_status(&mut self) -> Result<DeviceStatus, String> {
-        match self.ftdi.status() {
-            Ok(d) => return Ok(d),
-            Err(e) => {
-                return Err(format!("Could read device status. Error: {}", e));
-            }
+    /// Open every connected device. Returns one result per device found, so applications can
+    /// use the devices, that could be opened, and report the others. If the devices cannot be
+    /// listed at all, the only result is that error.
+    pub fn open_all() -> Vec<Result<OpenDMX, DmxError>> {
+        match Self::list_devices() {
+            Ok(list) => (0..list.len() as i32).map(OpenDMX::new).collect(),
+            Err(e) => vec![Err(e)],
         }
     }
 
-    /// Write local buffer to device.
-    /// This object keeps whether its internal state has changed or not and will only update device data
-    /// if the local buffer has changed since the last write action.
-    /// If you want to overwrite the device status regardless of the internal state set 'force' to true.
-    pub fn write(&mut self) -> Result<(), String> {
-        match self.ftdi.set_break_on() {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(format!("Could not set device break on. Error: {}", e));
-            }
-        }
+    /// Poll the list of connected devices in a background thread and send the current list with
+    /// every poll, e.g. to refresh a device picker. Compare the list with the previous one to
+    /// react on added or removed devices only. The thread ends with the first poll after the
+    /// returned receiver has been dropped.
+    pub fn watch_devices(interval: Duration) -> Receiver<Vec<OpenDmxDeviceInfo>> {
+        let list =
+            || Self::list_devices().map(|list| list.iter().map(OpenDmxDeviceInfo::from).collect());
+        Self::spawn_device_watcher(interval, list).0
+    }
 
-        match self.ftdi.set_break_off() {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(format!("Could not set device break off. Error: {}", e));
+    /// Poll the given function until the receiver has been dropped (see watch_devices()).
+    fn spawn_device_watcher<F>(
+        interval: Duration,
+        mut list: F,
+    ) -> (Receiver<Vec<OpenDmxDeviceInfo>>, JoinHandle<()>)
+    where
+        F: FnMut() -> Result<Vec<OpenDmxDeviceInfo>, DmxError> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            let mut last = Vec::new();
+            loop {
+                // Sending is the only way to notice a dropped receiver, so an unchanged list is
+                // send as well. If the devices cannot be listed, the last list is repeated.
+                if let Ok(list) = list() {
+                    last = list;
+                }
+                if sender.send(last.clone()).is_err() {
+                    break;
+                }
+
+                thread::sleep(interval);
             }
-        }
+        });
+
+        (receiver, handle)
+    }
 
-        match self.ftdi.write_all(&self.buffer) {
+    /// Retrieve data about the current device.
+    pub fn get_device_info(&self) -> &DeviceInfo {
+        &self.info
+    }
+
+    /// Same as get_device_info() but returns an owned copy, that does not depend on libftd2xx.
+    pub fn device_info_owned(&self) -> OpenDmxDeviceInfo {
+        OpenDmxDeviceInfo::from(&self.info)
+    }
+
+    /// Set or clear the break condition on the line. Fails with DmxError::Ftdi, see
+    /// DmxError::DeviceNotFound for telling an unplugged device from a transient failure.
+    pub fn set_break(&mut self, on: bool) -> Result<(), DmxError> {
+        match self.backend.set_break(on) {
             Ok(_) => Ok(()),
-            Err(e) => {
-                return Err(format!("Could not write data to device. Error: {}", e));
-            }
+            Err(e) if on => Err(DmxError::Ftdi("set device break on", e)),
+            Err(e) => Err(DmxError::Ftdi("set device break off", e)),
         }
     }
 
-    /// Reset the buffer to zero
-    pub fn reset_buffer(&mut self) {
-        self.buffer = [0; BUFFER_SIZE];
+    /// Get device status from the current device.
+    pub fn get_device_status(&mut self) -> Result<DeviceStatus, DmxError> {
+        match self.backend.status() {
+            Ok(d) => Ok(d),
+            Err(e) => Err(DmxError::Ftdi("read device status", e)),
+        }
     }
 
-    fn framesleep(timer: &Instant, frame_time: u128, granularity: TimerGranularity) {
-        match granularity {
-            TimerGranularity::Unknown => {
-                while timer.elapsed().as_millis() < frame_time {
-                    // Busy wait
-                }
-            }
-            TimerGranularity::Good => {
-                while timer.elapsed().as_millis() < frame_time {
-                    thread::sleep(Duration::from_millis(1));
-                }
+    /// Wait until the transmit queue of the device is empty, so the data written so far has been
+    /// sent on the line, or the timeout has elapsed. Returns whether the queue has been drained.
+    /// Closing the device discards data, that is still queued.
+    pub fn flush(&mut self, timeout: Duration) -> Result<bool, DmxError> {
+        let start = Instant::now();
+        loop {
+            if self.get_device_status()?.ammount_in_tx_queue == 0 {
+                return Ok(true);
             }
-            TimerGranularity::Bad => {
-                while timer.elapsed().as_millis() < frame_time {
-                    // Busy wait
-                }
+            if start.elapsed() >= timeout {
+                return Ok(false);
             }
+            thread::sleep(Duration::from_millis(1));
         }
     }
 
-    /// Create and initialize a new open dmx module with the given id.
-    /// This method also starts a new thread to continuously update the device.
-    /// The device is beeing controlled using the returned Sender instance.
-    ///
-    /// This is a port of the implementation in QLC+. See:
-    /// https://github.com/mcallegari/qlcplus/blob/master/plugins/dmxusb/src/enttecdmxusbopen.cpp
+    /// Write local buffer to device.
+    /// This object keeps whether its internal state has changed since the last write action
+    /// (see is_dirty()). Since dmx devices need continuous updates, the buffer is written
+    /// regardless of that state.
     ///
-    pub fn run(id: i32) -> (Sender<OpenDmxProtocol>, Receiver<OpenDmxProtocol>) {
-        let sender: Sender<OpenDmxProtocol>;
-        let receiver: Receiver<OpenDmxProtocol>;
-        (sender, receiver) = mpsc::channel();
+    /// The break and the mark after break are held for the configured times (see
+    /// OpenDmxBuilder::timing()). Returns the number of bytes written. A short write is logged as
+    /// a warning and usually indicates a troubled USB connection.
+    pub fn write(&mut self) -> Result<usize, DmxError> {
+        self.ensure_ready()?;
+        self.set_break(true)?;
+        thread::sleep(self.break_time);
+        self.set_break(false)?;
+        thread::sleep(self.mark_after_break);
+
+        self.write_frame()
+    }
+
+    /// Same as write() but without the break, for callers, that have already sent and timed the
+    /// break and the mark after break themselves.
+    pub fn write_frame(&mut self) -> Result<usize, DmxError> {
+        self.ensure_ready()?;
+
+        let frame = self.output_frame();
+        let frame = &frame[..self.frame_size.slots(&frame)];
+        match self.backend.write(frame) {
+            Ok(written) => {
+                if written < frame.len() {
+                    log::warn!(
+                        "Short write to dmx device: {} of {} bytes",
+                        written,
+                        frame.len()
+                    );
+                } else {
+                    self.dirty = false;
+                }
+                Ok(written)
+            }
+            Err(e) => Err(DmxError::Ftdi("write data to device", e)),
+        }
+    }
+
+    /// Whether the buffer has changed since the last write().
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Set the curve, that maps the stored values of the intensity channels to the transmitted
+    /// values. The buffer keeps the stored values, so get_dmx_value() returns what has been set.
+    pub fn set_intensity_curve(&mut self, curve: IntensityCurve) {
+        self.curve = curve;
+    }
+
+    /// Set the channels the intensity curve is applied to. Defaults to all channels. Channels
+    /// outside of the mask, that are intensity channels in the channel profile, become
+    /// ChannelKind::Attribute (see set_channel_profile()).
+    pub fn set_intensity_channels(&mut self, channels: ChannelMask) {
+        for channel in 1..=DMX_UNIVERSE_SIZE {
+            let kind = if channels.contains(channel) {
+                ChannelKind::Intensity
+            } else if self.channel_profile.is_intensity(channel) {
+                ChannelKind::Attribute
+            } else {
+                continue;
+            };
+            // The channel is in range.
+            let _ = self.channel_profile.set(channel, kind);
+        }
+    }
+
+    /// Set the kind of every channel. Only intensity channels are scaled by the intensity curve
+    /// and the soft start. Defaults to all channels being intensity channels.
+    pub fn set_channel_profile(&mut self, profile: ChannelProfile) {
+        self.channel_profile = profile;
+    }
+
+    /// The kind of every channel (see set_channel_profile()).
+    pub fn channel_profile(&self) -> &ChannelProfile {
+        &self.channel_profile
+    }
+
+    /// Channels 1 to 512 as they would be transmitted for the current buffer, i.e. with the
+    /// layers merged and the soft start and intensity curve applied. Nothing is transmitted,
+    /// e.g. for a visualizer showing the actual output instead of the stored values.
+    pub fn preview(&self) -> [u8; DMX_UNIVERSE_SIZE] {
+        let frame = self.output_frame();
+        let mut preview = [0; DMX_UNIVERSE_SIZE];
+        preview.copy_from_slice(&frame[1..]);
+        preview
+    }
+
+    /// The frame, that is transmitted for the current buffer. Channels above DMX_UNIVERSE_SIZE
+    /// are not part of it (see OpenDmxBuilder::max_channels()).
+    fn output_frame(&self) -> [u8; DMX_SLOT_COUNT] {
+        let mut frame = [0; DMX_SLOT_COUNT];
+        frame.copy_from_slice(&self.buffer[..DMX_SLOT_COUNT]);
+        self.layers.merge_into(&mut frame);
+        if self.curve != IntensityCurve::Linear || self.ramp_level != u8::MAX {
+            for (channel, value) in frame.iter_mut().enumerate() {
+                if self.channel_profile.is_intensity(channel) {
+                    let level = (*value as u16 * self.ramp_level as u16 + 127) / 255;
+                    *value = self.curve.apply(level as u8);
+                }
+            }
+        }
+        frame
+    }
+
+    /// Set the level of the intensity channels for the given time since the soft start began.
+    /// Returns whether the soft start has finished.
+    fn update_soft_start(&mut self, elapsed: Duration) -> bool {
+        let duration = match self.soft_start {
+            Some(d) if elapsed < d => d,
+            _ => {
+                self.ramp_level = u8::MAX;
+                return true;
+            }
+        };
+
+        self.ramp_level = (elapsed.as_secs_f64() / duration.as_secs_f64() * 255.0) as u8;
+        false
+    }
+
+    /// The interval between two frames transmitted by the run() thread, derived from the update
+    /// frequency.
+    pub fn frame_interval(&self) -> Duration {
+        // The update frequency is given in frames per 1000 seconds.
+        let frequency = self.update_frequency.get() as u64;
+        Duration::from_micros((1_000_000_000 + frequency / 2) / frequency)
+    }
+
+    /// Set the callback, that the run() thread calls after every frame, that has been written
+    /// successfully, e.g. to mirror the output to a visualizer. None disables it. The callback
+    /// runs on the timing thread between two frames, so it has to return quickly. Hand the frame
+    /// over to another thread for anything slow.
+    pub fn set_on_frame(&mut self, on_frame: Option<FrameCallback>) {
+        self.on_frame = on_frame;
+    }
+
+    /// Register an observer, that the run() thread calls with the new value, whenever the
+    /// buffered value of the given channel has changed since the previous frame, e.g. to trigger
+    /// a relay once a channel goes above a threshold. Observers are called after the frame has
+    /// been written, but still on the timing thread, so they have to return quickly. Channels 1
+    /// to DMX_UNIVERSE_SIZE are valid.
+    pub fn register_observer(
+        &mut self,
+        channel: usize,
+        observer: ChannelObserver,
+    ) -> Result<(), DmxError> {
+        if channel == 0 || channel >= self.buffer.len() {
+            return Err(DmxError::InvalidChannel(channel));
+        }
+        self.observers
+            .push((channel, self.buffer[channel], observer));
+
+        Ok(())
+    }
+
+    /// Set the number of channels transmitted with every frame. Fails if a fixed size is not
+    /// between 1 and 512 channels. Defaults to FrameSize::Full512.
+    pub fn set_frame_size(&mut self, frame_size: FrameSize) -> Result<(), DmxError> {
+        frame_size.validate()?;
+        self.frame_size = frame_size;
+
+        Ok(())
+    }
+
+    /// The number of channels transmitted with every frame.
+    pub fn frame_size(&self) -> FrameSize {
+        self.frame_size
+    }
+
+    /// Set the start code, that is transmitted in front of the channel data of every frame.
+    /// Defaults to NULL_START_CODE.
+    pub fn set_start_code(&mut self, start_code: u8) {
+        self.buffer[0] = start_code;
+        self.dirty = true;
+    }
+
+    /// The start code, that is transmitted in front of the channel data of every frame.
+    pub fn get_start_code(&self) -> u8 {
+        self.buffer[0]
+    }
+
+    /// Reset all channels to zero or the idle state (see set_reset_mode()). The start code is
+    /// preserved.
+    pub fn reset_buffer(&mut self) {
+        match &self.reset_mode {
+            ResetMode::Zero => self.buffer[1..].fill(0),
+            ResetMode::Idle(state) => {
+                self.buffer[1..DMX_SLOT_COUNT].copy_from_slice(&state[..]);
+                self.buffer[DMX_SLOT_COUNT..].fill(0);
+            }
+        }
+        self.dirty = true;
+    }
+
+    /// Set the values reset_buffer() and the blackout frame on drop set the channels to.
+    /// Defaults to ResetMode::Zero.
+    pub fn set_reset_mode(&mut self, mode: ResetMode) {
+        self.reset_mode = mode;
+    }
+
+    /// The values reset_buffer() sets the channels to (see set_reset_mode()).
+    pub fn reset_mode(&self) -> &ResetMode {
+        &self.reset_mode
+    }
+
+    /// Use the given state, where state[0] is channel 1, as the safe default of the rig. Same
+    /// as set_reset_mode(ResetMode::Idle(state)).
+    pub fn set_idle_state(&mut self, state: [u8; DMX_UNIVERSE_SIZE]) {
+        self.reset_mode = ResetMode::Idle(Box::new(state));
+    }
+
+    /// Set all channels to the given value, e.g. to test the cabling. The start code is
+    /// preserved.
+    pub fn set_all(&mut self, value: u8) {
+        self.buffer[1..].fill(value);
+        self.dirty = true;
+    }
+
+    /// Set the given channel to zero.
+    pub fn reset_channel(&mut self, channel: usize) -> Result<(), DmxError> {
+        self.set_dmx_value(channel, 0)
+    }
+
+    /// Set len channels beginning with start to zero. Nothing is changed if the range does not
+    /// fit into the buffer.
+    pub fn reset_range(&mut self, start: usize, len: usize) -> Result<(), DmxError> {
+        let end = match start.checked_add(len) {
+            Some(end) if end <= self.buffer.len() => end,
+            _ => return Err(DmxError::InvalidChannel(start.saturating_add(len))),
+        };
+        self.buffer[start..end].fill(0);
+        self.dirty = true;
+
+        Ok(())
+    }
+
+    /// Transmit a single frame with a proper break and mark after break. The break timing is
+    /// enforced here, so this is an alternative to run() for applications, that already have a
+    /// frame loop (e.g. game engines). The caller is responsible for calling tick() at a steady
+    /// rate (see frame_interval()), otherwise fixtures might flicker.
+    ///
+    /// Same as write(), but without the number of bytes written.
+    pub fn tick(&mut self) -> Result<(), DmxError> {
+        self.write().map(|_| ())
+    }
+
+    /// Transmit the given frame the given number of times at the update frequency of the device.
+    pub fn send_frames(
+        &mut self,
+        frame: &[u8; DMX_UNIVERSE_SIZE],
+        count: usize,
+    ) -> Result<(), DmxError> {
+        self.set_frame(frame);
+
+        for i in 0..count {
+            let now = Instant::now();
+            self.tick()?;
+
+            if i + 1 < count {
+                thread::sleep(self.frame_interval().saturating_sub(now.elapsed()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Open the device with the given id, transmit the given frame a few times
+    /// (SEND_ONCE_FRAMES), so the fixtures latch the values, and close the device again.
+    /// This is useful for scripts, that set a static scene and exit.
+    pub fn send_once(device_id: i32, frame: &[u8; DMX_UNIVERSE_SIZE]) -> Result<(), DmxError> {
+        Self::send_once_with(OpenDmxBuilder::new(device_id), frame, SEND_ONCE_FRAMES)
+    }
+
+    /// Same as send_once() but opens the device with the given configuration and transmits the
+    /// frame the given number of times.
+    pub fn send_once_with(
+        builder: OpenDmxBuilder,
+        frame: &[u8; DMX_UNIVERSE_SIZE],
+        count: usize,
+    ) -> Result<(), DmxError> {
+        let mut device = builder.build()?;
+        device.reset()?;
+        device.send_frames(frame, count)?;
+
+        // Close before the device is dropped, otherwise a blackout frame would be sent.
+        device.close()
+    }
+
+    /// Wait for at least the given duration. With a bad or unknown timer granularity sleeping
+    /// might not be precise, so busy wait instead.
+    fn hold(duration: Duration, granularity: TimerGranularity) {
+        let start = Instant::now();
+        match granularity {
+            TimerGranularity::Good => thread::sleep(duration),
+            TimerGranularity::Unknown | TimerGranularity::Bad => {
+                while start.elapsed() < duration {
+                    // Busy wait
+                }
+            }
+        }
+    }
+
+    /// The tick of the given clock closest to now and the microseconds from now until the next
+    /// tick, for ticks every frame_time microseconds after epoch.
+    fn align(epoch: Instant, now: Instant, frame_time: u128) -> (Instant, u128) {
+        let elapsed = now.saturating_duration_since(epoch).as_micros();
+        let ticks = (elapsed + frame_time / 2) / frame_time;
+        let tick = epoch + Duration::from_micros((ticks * frame_time) as u64);
+        let next = tick + Duration::from_micros(frame_time as u64);
+        (tick, next.saturating_duration_since(now).as_micros())
+    }
+
+    /// Wait until frame_time microseconds have elapsed since timer.
+    fn framesleep(timer: &Instant, frame_time: u128, granularity: TimerGranularity) {
+        match granularity {
+            TimerGranularity::Unknown => {
+                while timer.elapsed().as_micros() < frame_time {
+                    // Busy wait
+                }
+            }
+            TimerGranularity::Good => {
+                while timer.elapsed().as_micros() < frame_time {
+                    let remaining = frame_time - timer.elapsed().as_micros().min(frame_time);
+                    let remaining = Duration::from_micros(remaining as u64);
+                    thread::sleep(remaining.min(Duration::from_millis(1)));
+                }
+            }
+            TimerGranularity::Bad => {
+                while timer.elapsed().as_micros() < frame_time {
+                    // Busy wait
+                }
+            }
+        }
+    }
+
+    /// Create and initialize a new open dmx module with the given id.
+    /// This method also starts a new thread to continuously update the device.
+    /// The device is beeing controlled using the returned Sender instance. The returned snapshot
+    /// is updated after every frame and can be read from any thread.
+    ///
+    /// This is a port of the implementation in QLC+. See:
+    /// https://github.com/mcallegari/qlcplus/blob/master/plugins/dmxusb/src/enttecdmxusbopen.cpp
+    ///
+    pub fn run(
+        id: i32,
+    ) -> (
+        Sender<OpenDmxProtocol>,
+        Receiver<OpenDmxProtocol>,
+        SharedSnapshot,
+    ) {
+        Self::run_with(OpenDmxBuilder::new(id))
+    }
+
+    /// Same as run() but opens the device with the given configuration.
+    pub fn run_with(
+        builder: OpenDmxBuilder,
+    ) -> (
+        Sender<OpenDmxProtocol>,
+        Receiver<OpenDmxProtocol>,
+        SharedSnapshot,
+    ) {
+        Self::run_with_callback(builder, None)
+    }
+
+    /// Same as run_with() but calls on_frame after every frame (see set_on_frame()).
+    pub fn run_with_callback(
+        builder: OpenDmxBuilder,
+        on_frame: Option<FrameCallback>,
+    ) -> (
+        Sender<OpenDmxProtocol>,
+        Receiver<OpenDmxProtocol>,
+        SharedSnapshot,
+    ) {
+        let sender: Sender<OpenDmxProtocol>;
+        let receiver: Receiver<OpenDmxProtocol>;
+        (sender, receiver) = mpsc::channel();
 
         let sender2: Sender<OpenDmxProtocol>;
         let receiver2: Receiver<OpenDmxProtocol>;
         (sender2, receiver2) = mpsc::channel();
 
-        thread::spawn(move || {
-            // Wait for device to settle, in case the device was opened just recently.
-            // Also, measure whether timer granularity is OK
-            let mut now = Instant::now();
+        let snapshot: SharedSnapshot = Arc::new(RwLock::new(UniverseSnapshot::default()));
+        let snapshot2 = snapshot.clone();
+
+        thread::spawn(move || {
+            builder.pin_current_thread();
+
+            let mut device = match builder.build() {
+                Ok(d) => d,
+                Err(e) => return Self::report_error(&sender2, e),
+            };
+            device.set_on_frame(on_frame);
+            Self::worker(device, receiver, sender2, snapshot2);
+        });
+
+        (sender, receiver2, snapshot)
+    }
+
+    /// Same as run() but the command channel holds at most capacity commands. Once the channel is
+    /// full, send() blocks and try_send() fails with TrySendError::Full until the thread catches
+    /// up. The thread handles at most MAX_COMMANDS_PER_FRAME commands per frame, so a producer,
+    /// that is faster than that, is slowed down instead of filling up the memory.
+    ///
+    /// With run() senders never block, but the queue can grow without limit.
+    pub fn run_bounded(
+        id: i32,
+        capacity: usize,
+    ) -> (
+        SyncSender<OpenDmxProtocol>,
+        Receiver<OpenDmxProtocol>,
+        SharedSnapshot,
+    ) {
+        let builder = OpenDmxBuilder::new(id);
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let (sender2, receiver2) = mpsc::channel();
+
+        let snapshot: SharedSnapshot = Arc::new(RwLock::new(UniverseSnapshot::default()));
+        let snapshot2 = snapshot.clone();
+
+        thread::spawn(move || {
+            builder.pin_current_thread();
+
+            let device = match builder.build() {
+                Ok(d) => d,
+                Err(e) => return Self::report_error(&sender2, e),
+            };
+            Self::worker(device, receiver, sender2, snapshot2);
+        });
+
+        (sender, receiver2, snapshot)
+    }
+
+    /// Same as run_bounded() but uses this already opened device.
+    pub fn spawn_bounded(
+        self,
+        capacity: usize,
+    ) -> (
+        SyncSender<OpenDmxProtocol>,
+        Receiver<OpenDmxProtocol>,
+        SharedSnapshot,
+    ) {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let (sender2, receiver2) = mpsc::channel();
+
+        let snapshot: SharedSnapshot = Arc::new(RwLock::new(UniverseSnapshot::default()));
+        let snapshot2 = snapshot.clone();
+
+        thread::spawn(move || {
+            Self::worker(self, receiver, sender2, snapshot2);
+        });
+
+        (sender, receiver2, snapshot)
+    }
+
+    /// Same as run() but uses this already opened device.
+    pub fn spawn(
+        self,
+    ) -> (
+        Sender<OpenDmxProtocol>,
+        Receiver<OpenDmxProtocol>,
+        SharedSnapshot,
+    ) {
+        let (sender, receiver) = mpsc::channel();
+        let (sender2, receiver2) = mpsc::channel();
+
+        let snapshot: SharedSnapshot = Arc::new(RwLock::new(UniverseSnapshot::default()));
+        let snapshot2 = snapshot.clone();
+
+        thread::spawn(move || {
+            Self::worker(self, receiver, sender2, snapshot2);
+        });
+
+        (sender, receiver2, snapshot)
+    }
+
+    /// Wait for device to settle, in case the device was opened just recently.
+    /// Also, measure whether timer granularity is OK.
+    fn settle(&self) -> TimerGranularity {
+        let start = Instant::now();
+        thread::sleep(self.settle_delay);
+
+        // Without a settle delay there is nothing to measure, assume the timer is fine.
+        if !self.settle_delay.is_zero() && start.elapsed() > self.settle_delay * 3 {
+            TimerGranularity::Bad
+        } else {
+            TimerGranularity::Good
+        }
+    }
+
+    /// Tell the caller of run(), why the thread stopped before it started transmitting.
+    fn report_error<S: ResponseSender>(sender2: &S, e: DmxError) {
+        log::error!("Could not start dmx device: {}", e);
+        match sender2.notify(OpenDmxProtocol::Error(Box::new(e))) {
+            Ok(_) => {}
+            Err(_) => {
+                log::warn!("Could not send an error notification.")
+            }
+        }
+    }
+
+    /// Continuously update the device until a Stop command is received.
+    fn worker<C: Into<OpenDmxProtocol>, S: ResponseSender>(
+        mut device: OpenDMX,
+        receiver: Receiver<C>,
+        sender2: S,
+        snapshot: SharedSnapshot,
+    ) {
+        let granularity = device.settle();
+        let mut now = Instant::now();
+
+        let mut running = true;
+        let mut paused = false;
+        let mut frames: u64 = 0;
+        let mut cues: Option<CuePlayer> = None;
+        // The running test pattern, its start and the values to restore afterwards.
+        let mut test_pattern: Option<(TestPattern, Instant, [u8; DMX_UNIVERSE_SIZE])> = None;
+        let mut jitter = JitterMeter::default();
+        let mut last_frame: Option<Instant> = None;
+        let mut timing_degraded = false;
+        let mut recent_frames: VecDeque<[u8; DMX_UNIVERSE_SIZE]> =
+            VecDeque::with_capacity(device.recent_frames);
+        // Frames the current buffer still has to be transmitted and a command, that has to wait
+        // for them (see OpenDmxBuilder::latch_frames()).
+        let mut latching: usize = 0;
+        let mut delayed: Option<OpenDmxProtocol> = None;
+        // All senders are gone, no more commands can arrive.
+        let mut control_lost = false;
+        let mut clock: Option<Instant> = None;
+        // Frames waiting for their time, the earliest first.
+        let mut scheduled: Vec<(Instant, Box<[u8; DMX_UNIVERSE_SIZE]>)> = Vec::new();
+        let mut events = EventLog::default();
+
+        match device.reset() {
+            Ok(_) => {}
+            Err(e) => return Self::report_error(&sender2, e.or_not_found()),
+        }
+
+        // Ramp up the intensity channels with the first frames.
+        let soft_start = Instant::now();
+        let mut soft_starting = !device.update_soft_start(Duration::ZERO);
+
+        let mut last_change = Instant::now();
+        let started = Instant::now();
+        let mut failed_frames: u64 = 0;
+        let mut idle = false;
+
+        match sender2.notify(OpenDmxProtocol::Started(device.frame_interval())) {
+            Ok(_) => {}
+            Err(_) => {
+                log::warn!("Could not send a started notification.")
+            }
+        }
+        events.record(EventKind::Started);
+
+        while running {
+            // Receive incomming commands and update our buffer
+            let mut handled = 0;
+            let latched = latching == 0;
+            while handled < MAX_COMMANDS_PER_FRAME && !control_lost {
+                let cmd = match delayed.take() {
+                    Some(cmd) => cmd,
+                    None => match receiver.try_recv() {
+                        Ok(cmd) => cmd.into(),
+                        Err(TryRecvError::Empty) => break,
+                        // All senders are gone, nobody can stop the thread anymore.
+                        Err(TryRecvError::Disconnected) => match device.on_control_loss {
+                            OnControlLoss::Hold => {
+                                control_lost = true;
+                                events.record(EventKind::ControlLost);
+                                break;
+                            }
+                            OnControlLoss::Blackout => {
+                                control_lost = true;
+                                events.record(EventKind::ControlLost);
+                                cues = None;
+                                test_pattern = None;
+                                paused = false;
+                                device.layers = Layers::default();
+                                device.reset_buffer();
+                                break;
+                            }
+                            OnControlLoss::Stop => OpenDmxProtocol::Stop,
+                        },
+                    },
+                };
+                handled += 1;
+
+                if paused && cmd.changes_buffer() {
+                    continue;
+                }
+
+                // Changes within one frame are transmitted together.
+                if cmd.changes_buffer() {
+                    if !latched {
+                        delayed = Some(cmd);
+                        break;
+                    }
+                    latching = device.latch_frames;
+                }
+
+                match cmd {
+                    OpenDmxProtocol::SetValue(channel, value) => {
+                        let _ = device.set_dmx_value(channel, value);
+                    }
+                    OpenDmxProtocol::Bump(channel, delta) => {
+                        let _ = device.bump_dmx_value(channel, delta);
+                    }
+                    OpenDmxProtocol::SendFrame(frame) => {
+                        device.set_frame(&frame);
+                    }
+                    OpenDmxProtocol::Delta(delta) => {
+                        let _ = device.apply_delta(&delta);
+                    }
+                    OpenDmxProtocol::SetLayerValue(layer, channel, value) => {
+                        let _ = device.set_layer_value(layer, channel, value);
+                    }
+                    OpenDmxProtocol::SetLayerMode(layer, mode) => {
+                        device.set_layer_mode(layer, mode);
+                    }
+                    OpenDmxProtocol::ClearLayer(layer) => {
+                        device.clear_layer(layer);
+                    }
+                    OpenDmxProtocol::Stop => {
+                        running = false;
+                        break;
+                    }
+                    OpenDmxProtocol::StopHold => {
+                        device.blackout_on_drop = false;
+                        running = false;
+                        break;
+                    }
+                    OpenDmxProtocol::Reset => match device.reset() {
+                        Ok(_) => events.record(EventKind::Reset),
+                        Err(e) => {
+                            events.record(EventKind::ResetFailed(e));
+                            log::warn!("Error resetting a DMX-Device.")
+                        }
+                    },
+                    OpenDmxProtocol::ResetBuffer => {
+                        device.reset_buffer();
+                    }
+                    OpenDmxProtocol::ResetToIdle => {
+                        cues = None;
+                        test_pattern = None;
+                        device.layers = Layers::default();
+                        device.reset_buffer();
+                    }
+                    OpenDmxProtocol::SetAll(value) => {
+                        device.set_all(value);
+                    }
+                    OpenDmxProtocol::StartCode(start_code) => {
+                        device.set_start_code(start_code);
+                        events.record(EventKind::StartCodeChanged(start_code));
+                    }
+                    OpenDmxProtocol::FrameSize(frame_size) => {
+                        if device.set_frame_size(frame_size).is_ok() {
+                            events.record(EventKind::FrameSizeChanged(frame_size));
+                        }
+                    }
+                    OpenDmxProtocol::ResetRange(start, len) => {
+                        let _ = device.reset_range(start, len);
+                    }
+                    OpenDmxProtocol::Purge => match device.purge() {
+                        Ok(_) => {}
+                        Err(_) => {
+                            log::warn!("Error purging a DMX-Device.")
+                        }
+                    },
+                    OpenDmxProtocol::Pause => {
+                        paused = true;
+                        events.record(EventKind::Paused);
+                        match sender2.notify(OpenDmxProtocol::Paused) {
+                            Ok(_) => {}
+                            Err(_) => {
+                                log::warn!("Could not send a pause acknowledgement.")
+                            }
+                        }
+                    }
+                    OpenDmxProtocol::Resume => {
+                        paused = false;
+                        events.record(EventKind::Resumed);
+                        match sender2.notify(OpenDmxProtocol::Resumed) {
+                            Ok(_) => {}
+                            Err(_) => {
+                                log::warn!("Could not send a resume acknowledgement.")
+                            }
+                        }
+                    }
+                    OpenDmxProtocol::Idle => {
+                        idle = true;
+                        events.record(EventKind::Idle);
+                    }
+                    OpenDmxProtocol::Engage => {
+                        idle = false;
+                        events.record(EventKind::Engaged);
+                    }
+                    OpenDmxProtocol::GetPhase => {
+                        let phase = OpenDmxProtocol::Phase {
+                            elapsed: now.elapsed(),
+                            frame: device.frame_interval(),
+                        };
+                        match sender2.notify(phase) {
+                            Ok(_) => {}
+                            Err(_) => {
+                                log::warn!("Could not send a phase response.")
+                            }
+                        }
+                    }
+                    OpenDmxProtocol::GetRecentFrames => {
+                        let frames =
+                            OpenDmxProtocol::RecentFrames(recent_frames.iter().copied().collect());
+                        match sender2.notify(frames) {
+                            Ok(_) => {}
+                            Err(_) => {
+                                log::warn!("Could not send a recent frames response.")
+                            }
+                        }
+                    }
+                    OpenDmxProtocol::RecentFrames(_) => {}
+                    OpenDmxProtocol::GetStats => {
+                        let stats = OpenDmxProtocol::Stats(Box::new(FrameStats {
+                            frames,
+                            failed_frames,
+                            running: started.elapsed(),
+                        }));
+                        match sender2.notify(stats) {
+                            Ok(_) => {}
+                            Err(_) => {
+                                log::warn!("Could not send a stats response.")
+                            }
+                        }
+                    }
+                    OpenDmxProtocol::Stats(_) => {}
+                    OpenDmxProtocol::GetEventLog => {
+                        match sender2.notify(OpenDmxProtocol::EventLog(events.entries())) {
+                            Ok(_) => {}
+                            Err(_) => {
+                                log::warn!("Could not send an event log response.")
+                            }
+                        }
+                    }
+                    OpenDmxProtocol::EventLog(_) => {}
+                    OpenDmxProtocol::ListDevices => {
+                        let mut payload = OpenDmxProtocol::DeviceList(Vec::new());
+                        if let Ok(list) = Self::list_devices() {
+                            payload = OpenDmxProtocol::DeviceList(list);
+                        }
+
+                        match sender2.notify(payload) {
+                            Ok(_) => {}
+                            Err(_) => {
+                                log::warn!("Could not send a list devices response.")
+                            }
+                        }
+                    }
+                    OpenDmxProtocol::DeviceList(_device_infos) => {}
+                    OpenDmxProtocol::Started(_frame_interval) => {}
+                    OpenDmxProtocol::Phase { .. } => {}
+                    OpenDmxProtocol::Paused => {}
+                    OpenDmxProtocol::Resumed => {}
+                    OpenDmxProtocol::PlayCues(list) => {
+                        let mut from = [0; DMX_UNIVERSE_SIZE];
+                        from.copy_from_slice(&device.buffer[1..DMX_SLOT_COUNT]);
+
+                        cues = CuePlayer::new(list, from, Instant::now());
+                        if cues.is_some() {
+                            match sender2.notify(OpenDmxProtocol::CueStarted(0)) {
+                                Ok(_) => {}
+                                Err(_) => {
+                                    log::warn!("Could not send a cue started notification.")
+                                }
+                            }
+                        }
+                    }
+                    OpenDmxProtocol::StopCues => {
+                        cues = None;
+                    }
+                    OpenDmxProtocol::SyncClock(epoch) => {
+                        clock = Some(epoch);
+                        events.record(EventKind::ClockSynced);
+                    }
+                    OpenDmxProtocol::ScheduleFrame(at, frame) => {
+                        scheduled.push((at, frame));
+                        scheduled.sort_by_key(|(at, _)| *at);
+                    }
+                    OpenDmxProtocol::SnapToTarget => {
+                        if let Some(player) = cues.as_mut() {
+                            player.snap(Instant::now());
+                        }
+                    }
+                    OpenDmxProtocol::TestPattern(pattern) => {
+                        cues = None;
+
+                        // Keep the values from before the first pattern.
+                        let manual = match test_pattern {
+                            Some((_, _, manual)) => manual,
+                            None => {
+                                let mut manual = [0; DMX_UNIVERSE_SIZE];
+                                manual.copy_from_slice(&device.buffer[1..DMX_SLOT_COUNT]);
+                                manual
+                            }
+                        };
+                        test_pattern = Some((pattern, Instant::now(), manual));
+                    }
+                    OpenDmxProtocol::StopTestPattern => {
+                        if let Some((_, _, manual)) = test_pattern.take() {
+                            device.set_frame(&manual);
+                        }
+                    }
+                    OpenDmxProtocol::CueStarted(_) => {}
+                    OpenDmxProtocol::LineError(_) => {}
+                    OpenDmxProtocol::TimingDegraded(_) => {}
+                    OpenDmxProtocol::Error(_) => {}
+                }
+            }
+
+            // Update device.
+            latching = latching.saturating_sub(1);
+            if idle {
+                // Precision does not matter without frames, so do not busy wait.
+                last_frame = None;
+                thread::sleep(device.frame_interval());
+                continue;
+            }
+            now = Instant::now();
+            if let Some(last) = last_frame {
+                jitter.record(now - last);
+            }
+            last_frame = Some(now);
+
+            // The interval changes with the adaptive refresh mode, so there is nothing to compare.
+            if !timing_degraded && device.refresh_mode == RefreshMode::Steady {
+                if let Some(stats) = jitter.stats(device.frame_interval()) {
+                    if stats.is_degraded() {
+                        timing_degraded = true;
+                        events.record(EventKind::TimingDegraded);
+                        match sender2.notify(OpenDmxProtocol::TimingDegraded(Box::new(stats))) {
+                            Ok(_) => {}
+                            Err(_) => {
+                                log::warn!("Could not send a timing degraded notification.")
+                            }
+                        }
+                    }
+                }
+            }
+            if soft_starting {
+                soft_starting = !device.update_soft_start(soft_start.elapsed());
+            }
+            if let (false, Some(universe)) = (paused, device.shared_universe.clone()) {
+                // A blackout after control loss must not be overwritten by the last universe.
+                if !(control_lost && device.on_control_loss == OnControlLoss::Blackout) {
+                    match universe.lock() {
+                        Ok(frame) => {
+                            // Only changes mark the buffer dirty, like commands do.
+                            if device.buffer[1..DMX_SLOT_COUNT] != frame[..] {
+                                device.set_frame(&frame);
+                            }
+                        }
+                        Err(_) => {
+                            log::error!(
+                                "The shared universe is poisoned. Stopping the DMX-Device."
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+
+            // With a shared clock all devices agree on the time of this frame, even if they woke
+            // up a little apart.
+            let tick = match clock {
+                Some(epoch) => Self::align(epoch, now, device.frame_interval().as_micros()).0,
+                None => now,
+            };
+            while let (false, Some((at, _))) = (paused, scheduled.first()) {
+                if *at > tick {
+                    break;
+                }
+                let (_, frame) = scheduled.remove(0);
+                device.set_frame(&frame);
+            }
+
+            if let (false, Some(player)) = (paused, cues.as_mut()) {
+                let step = player.update(now);
+                device.set_frame(&step.frame);
+
+                if let Some(index) = step.started {
+                    match sender2.notify(OpenDmxProtocol::CueStarted(index)) {
+                        Ok(_) => {}
+                        Err(_) => {
+                            log::warn!("Could not send a cue started notification.")
+                        }
+                    }
+                }
+
+                if step.finished {
+                    cues = None;
+                }
+            }
+
+            if let (false, Some((pattern, started, _))) = (paused, test_pattern.as_ref()) {
+                device.set_frame(&pattern.frame(now.duration_since(*started)));
+            }
+
+            if device.is_dirty() || soft_starting {
+                last_change = now;
+            }
+            // The DMX frame time duration in microseconds.
+            let frame_time: u128 = device
+                .refresh_mode
+                .frame_interval(device.frame_interval(), now - last_change)
+                .as_micros();
+            let frame_time = match clock {
+                Some(epoch) => Self::align(epoch, now, frame_time).1,
+                None => frame_time,
+            };
+
+            let mut result = device.set_break(true);
+            if result.is_ok() {
+                Self::hold(device.break_time, granularity);
+                result = device.set_break(false);
+            }
+            match result.map_err(DmxError::or_not_found) {
+                Ok(_) => {}
+                // The device has been unplugged, every further frame would fail as well.
+                Err(DmxError::DeviceNotFound) => {
+                    log::error!("The dmx device has been disconnected.");
+                    events.record(EventKind::Disconnected);
+                    match sender2.notify(OpenDmxProtocol::Error(Box::new(DmxError::DeviceNotFound)))
+                    {
+                        Ok(_) => {}
+                        Err(_) => {
+                            log::warn!("Could not send an error notification.")
+                        }
+                    }
+                    break;
+                }
+                Err(_) => {
+                    failed_frames += 1;
+                    Self::framesleep(&now, frame_time, granularity);
+                    continue;
+                }
+            }
+
+            Self::hold(device.mark_after_break, granularity);
+
+            if device.write_frame().is_ok() {
+                let frame = device.output_frame();
+                if let Ok(mut s) = snapshot.write() {
+                    s.update(&frame);
+                }
+
+                let mut channels = [0; DMX_UNIVERSE_SIZE];
+                channels.copy_from_slice(&frame[1..]);
+                if let Some(on_frame) = device.on_frame.as_mut() {
+                    on_frame(&channels);
+                }
+                for (channel, last, observer) in device.observers.iter_mut() {
+                    let value = device.buffer[*channel];
+                    if value != *last {
+                        *last = value;
+                        observer(value);
+                    }
+                }
+
+                if device.recent_frames > 0 {
+                    if recent_frames.len() == device.recent_frames {
+                        recent_frames.pop_front();
+                    }
+                    recent_frames.push_back(channels);
+                }
+
+                frames += 1;
+                if frames.is_multiple_of(STATUS_POLL_FRAMES) {
+                    if let Ok(status) = device.get_device_status() {
+                        if status.event_status & LINE_STATUS_EVENT != 0 {
+                            events.record(EventKind::LineError(status));
+                            match sender2.notify(OpenDmxProtocol::LineError(status)) {
+                                Ok(_) => {}
+                                Err(_) => {
+                                    log::warn!("Could not send a line error notification.")
+                                }
+                            }
+                        }
+                    }
+                }
+            } else {
+                failed_frames += 1;
+            }
+
+            // Keep the line idle for a consistent time before the next break.
+            Self::hold(device.mark_before_break, granularity);
+
+            // At the idle rate of the adaptive refresh mode the next command is transmitted
+            // right away instead of after the idle interval.
+            let active = device
+                .refresh_mode
+                .frame_interval(device.frame_interval(), Duration::ZERO)
+                .as_micros();
+            if clock.is_none() && frame_time > active && delayed.is_none() && !control_lost {
+                let remaining =
+                    Duration::from_micros(frame_time as u64).saturating_sub(now.elapsed());
+                if let Ok(cmd) = receiver.recv_timeout(remaining) {
+                    delayed = Some(cmd.into());
+                    Self::framesleep(&now, active, granularity);
+                    continue;
+                }
+            }
+            Self::framesleep(&now, frame_time, granularity);
+        }
+    }
+}
+
+/// Summarizes the device without dumping the whole buffer.
+impl fmt::Debug for OpenDMX {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpenDMX")
+            .field("serial", &self.info.serial_number)
+            .field("description", &self.info.description)
+            .field("state", &self.state)
+            .field("baud_rate", &self.baud_rate)
+            .field("bits_per_word", &self.bits_per_word)
+            .field("stop_bits", &self.stop_bits)
+            .field("parity", &self.parity_none)
+            .field("update_frequency", &self.update_frequency)
+            .field(
+                "driver_version",
+                &self.driver_version.map(|v| v.to_string()),
+            )
+            .field("library_version", &self.library_version)
+            .field("channels", &self.channel_count())
+            .field("non_zero_channels", &self.active_summary().0)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for OpenDMX {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}), {} of {} channels set",
+            self.info.description,
+            self.info.serial_number,
+            self.active_summary().0,
+            self.channel_count()
+        )
+    }
+}
+
+/// A device must be closed once it´s not used anymore. If not, the device will be blocked.
+/// Before closing, a single blackout frame is sent. Errors are only logged, since the device
+/// might already be gone, and the device is closed in any case.
+///
+/// A device, that has never been reset, sends no blackout frame: it has not transmitted
+/// anything, so there is nothing to black out, and the line is not configured for dmx yet.
+impl Drop for OpenDMX {
+    fn drop(&mut self) {
+        if self.blackout_on_drop {
+            self.reset_buffer();
+            self.set_start_code(NULL_START_CODE);
+
+            match self.tick() {
+                Ok(_) => {}
+                Err(e) => {
+                    log::debug!("Could not send blackout frame. Error: {}", e);
+                }
+            }
+        }
+
+        // Let the last frame reach the fixtures instead of discarding it with the close.
+        if self.state == ConnectionState::Ready {
+            match self.flush(DRAIN_TIMEOUT) {
+                Ok(true) => {}
+                Ok(false) => log::debug!("Transmit queue did not drain before closing."),
+                Err(e) => {
+                    log::debug!("Could not drain transmit queue. Error: {}", e);
+                }
+            }
+        }
+
+        match self.close() {
+            Ok(_) => {}
+            Err(e) => {
+                log::debug!("Could not close open_dmx device. Error: {}", e);
+            }
+        }
+    }
+}
+
+/// Tests cannot run in parallel, because in most cases we got only one device and
+/// this library needs exclusive access to the device.
+///
+/// Run tests with:
+/// cargo test -- --nocapture --test-threads=1
+#[cfg(test)]
+mod tests {
+    use libftd2xx::DeviceType;
+
+    use super::*;
+
+    #[test]
+    fn num_devices_test() {
+        let subject = OpenDMX::get_num_of_devices().unwrap();
+        assert_eq!(subject, 1);
+    }
+
+    #[test]
+    fn protocol_size_test() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<OpenDmxProtocol>();
+        assert_send_sync::<DmxCommand>();
+        assert_send_sync::<DmxEvent>();
+
+        assert!(std::mem::size_of::<OpenDmxProtocol>() <= 32);
+        assert!(std::mem::size_of::<DmxCommand>() <= 32);
+        assert!(std::mem::size_of::<DmxEvent>() <= 32);
+    }
+
+    #[test]
+    fn local_buffer_test() {
+        let mut subject = OpenDMX::new(0).unwrap();
+        // Check default
+        assert_eq!(subject.get_dmx_value(0).unwrap(), 0);
+
+        // Set a value...
+        subject.set_dmx_value(0, 1).unwrap();
+        assert_eq!(subject.get_dmx_value(0).unwrap(), 1);
+
+        // ... overwrite the value again.
+        subject.set_dmx_value(0, 0).unwrap();
+        assert_eq!(subject.get_dmx_value(0).unwrap(), 0);
+
+        // Test invalid channel numbers.
+        let e = subject.set_dmx_value(DMX_SLOT_COUNT, 10);
+        assert_eq!(e, Err(DmxError::InvalidChannel(DMX_SLOT_COUNT)));
+
+        let e2 = subject.get_dmx_value(DMX_SLOT_COUNT);
+        assert_eq!(e2, Err(DmxError::InvalidChannel(DMX_SLOT_COUNT)));
+    }
+
+    #[test]
+    fn reset_range_test() {
+        let mut subject = OpenDMX::new(0).unwrap();
+        for channel in 1..=4 {
+            subject.set_dmx_value(channel, 255).unwrap();
+        }
+
+        subject.reset_channel(1).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 0);
+
+        subject.reset_range(2, 2).unwrap();
+        assert_eq!(subject.get_dmx_value(2).unwrap(), 0);
+        assert_eq!(subject.get_dmx_value(3).unwrap(), 0);
+        assert_eq!(subject.get_dmx_value(4).unwrap(), 255);
+
+        // Ranges that do not fit into the buffer leave the buffer untouched.
+        let e = subject.reset_range(4, DMX_SLOT_COUNT);
+        assert_eq!(e, Err(DmxError::InvalidChannel(DMX_SLOT_COUNT + 4)));
+        assert_eq!(subject.get_dmx_value(4).unwrap(), 255);
+    }
+
+    #[test]
+    fn mock_local_buffer_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        assert_eq!(subject.get_device_info().description, "Mock");
+        assert_eq!(subject.channel_count(), DMX_UNIVERSE_SIZE);
+
+        subject.set_dmx_value(1, 10).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 10);
+
+        let e = subject.set_dmx_value(DMX_SLOT_COUNT, 10);
+        assert_eq!(e, Err(DmxError::InvalidChannel(DMX_SLOT_COUNT)));
+    }
+
+    #[test]
+    fn mock_write_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        assert_eq!(backend.resets(), 1);
+        assert_eq!(backend.usb_transfer_size(), Some(DEFAULT_USB_TRANSFER_SIZE));
+
+        subject.set_dmx_value(1, 255).unwrap();
+        subject.write().unwrap();
+
+        let frame = backend.last_write().unwrap();
+        assert_eq!(frame.len(), DMX_SLOT_COUNT);
+        assert_eq!(frame[0], 0);
+        assert_eq!(frame[1], 255);
+        assert!(!backend.is_break_on());
+    }
+
+    #[test]
+    fn mock_drop_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        subject.set_dmx_value(1, 255).unwrap();
+        drop(subject);
+
+        // Dropping the device sends a blackout frame and closes the device.
+        assert_eq!(backend.last_write().unwrap(), vec![0; DMX_SLOT_COUNT]);
+        assert!(backend.is_closed());
+    }
+
+    #[test]
+    fn mock_frame_interval_test() {
+        let subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        // The default update frequency results in 40 frames per second.
+        assert_eq!(subject.frame_interval(), Duration::from_millis(25));
+    }
+
+    #[test]
+    fn align_test() {
+        let epoch = Instant::now();
+        let frame_time = Duration::from_millis(20);
+
+        // A frame, that starts a little late or early, still belongs to the closest tick.
+        let (tick, remaining) = OpenDMX::align(
+            epoch,
+            epoch + Duration::from_millis(41),
+            frame_time.as_micros(),
+        );
+        assert_eq!(tick, epoch + Duration::from_millis(40));
+        assert_eq!(remaining, 19000);
+
+        let (tick, remaining) = OpenDMX::align(
+            epoch,
+            epoch + Duration::from_millis(39),
+            frame_time.as_micros(),
+        );
+        assert_eq!(tick, epoch + Duration::from_millis(40));
+        assert_eq!(remaining, 21000);
+    }
+
+    #[test]
+    fn framesleep_test() {
+        // Frames shorter than a millisecond are paced as well.
+        let frame_time = Duration::from_micros(300);
+        for granularity in [
+            TimerGranularity::Unknown,
+            TimerGranularity::Good,
+            TimerGranularity::Bad,
+        ] {
+            let start = Instant::now();
+            for frame in 1..=5 {
+                let now = Instant::now();
+                OpenDMX::framesleep(&now, frame_time.as_micros(), granularity);
+                assert!(now.elapsed() >= frame_time);
+                assert!(start.elapsed() >= frame_time * frame);
+            }
+        }
+
+        let subject = OpenDMX::builder(0)
+            .update_frequency(UpdateFrequency::MAX)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        assert_eq!(subject.frame_interval(), Duration::from_micros(22727));
+    }
+
+    #[test]
+    fn mock_update_frequency_test() {
+        let subject = OpenDMX::builder(0)
+            .update_frequency(UpdateFrequency::new(20000).unwrap())
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        assert_eq!(subject.frame_interval(), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn mock_start_code_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        assert_eq!(subject.get_start_code(), NULL_START_CODE);
+
+        subject.set_start_code(TEXT_START_CODE);
+        subject.set_dmx_value(1, 255).unwrap();
+        subject.write().unwrap();
+        assert_eq!(backend.last_write().unwrap()[0], TEXT_START_CODE);
+
+        // Resetting the buffer keeps the start code.
+        subject.reset_buffer();
+        assert_eq!(subject.get_start_code(), TEXT_START_CODE);
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 0);
+
+        // The blackout frame on drop is a regular dmx frame.
+        drop(subject);
+        assert_eq!(backend.last_write().unwrap()[0], NULL_START_CODE);
+    }
+
+    #[test]
+    fn mock_intensity_curve_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        let mut intensity = ChannelMask::empty();
+        intensity.insert(1).unwrap();
+        subject.set_intensity_channels(intensity);
+        subject.set_intensity_curve(IntensityCurve::Square);
+
+        subject.set_dmx_value(1, 128).unwrap();
+        subject.set_dmx_value(2, 128).unwrap();
+        subject.write().unwrap();
+
+        // Only the intensity channel is mapped, the buffer keeps the stored value.
+        let frame = backend.last_write().unwrap();
+        assert_eq!(frame[1], 64);
+        assert_eq!(frame[2], 128);
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 128);
+        assert_eq!(
+            subject.channel_profile().kind(2),
+            Ok(ChannelKind::Attribute)
+        );
+    }
+
+    #[test]
+    fn mock_channel_profile_test() {
+        let backend = MockBackend::new();
+        let mut profile = ChannelProfile::new();
+        profile.set_range(2, 2, ChannelKind::Position).unwrap();
+        let mut subject = OpenDmxBuilder::new(0)
+            .channel_profile(profile)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        subject.reset().unwrap();
+        subject.set_intensity_curve(IntensityCurve::Square);
+
+        subject.set_all(128);
+        subject.write().unwrap();
+
+        // Pan and tilt are not scaled.
+        let frame = backend.last_write().unwrap();
+        assert_eq!(&frame[1..5], &[64, 128, 128, 64]);
+    }
+
+    #[test]
+    fn mock_preview_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        subject.set_intensity_curve(IntensityCurve::Square);
+        subject.set_dmx_value(1, 128).unwrap();
+        subject.set_layer_value(1, 2, 200).unwrap();
+
+        let writes = backend.writes().len();
+        let preview = subject.preview();
+        assert_eq!(backend.writes().len(), writes);
+        assert_eq!(preview[0], 64);
+        assert!(preview[1] > 0);
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 128);
+
+        subject.write().unwrap();
+        assert_eq!(backend.last_write().unwrap()[1..], preview[..]);
+    }
+
+    #[test]
+    fn mock_flow_control_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::builder(0)
+            .flow_control(FlowControl::RtsCts)
+            .rts(RtsState::Set)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        subject.reset().unwrap();
+
+        assert_eq!(backend.flow_control(), FlowControl::RtsCts);
+        assert_eq!(backend.rts(), RtsState::Set);
+    }
+
+    #[test]
+    fn mock_device_profile_test() {
+        let mut backend = MockBackend::with_device_type(DeviceType::FT232H);
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        assert_eq!(backend.usb_transfer_size(), Some(1024));
+        assert_eq!(backend.latency_timer().unwrap(), Duration::from_millis(1));
+
+        // The builder settings take precedence over the profile.
+        let mut backend = MockBackend::with_device_type(DeviceType::FT232H);
+        let mut subject = OpenDMX::builder(0)
+            .latency_timer(Duration::from_millis(4))
+            .usb_transfer_size(None)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        subject.reset().unwrap();
+        assert_eq!(backend.usb_transfer_size(), None);
+        assert_eq!(backend.latency_timer().unwrap(), Duration::from_millis(4));
+    }
+
+    #[test]
+    fn mock_set_frame_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        subject.set_start_code(TEXT_START_CODE);
+
+        let mut frame = [0; DMX_UNIVERSE_SIZE];
+        frame[0] = 1;
+        frame[DMX_UNIVERSE_SIZE - 1] = 2;
+        subject.set_frame(&frame);
+
+        assert_eq!(subject.get_start_code(), TEXT_START_CODE);
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 1);
+        assert_eq!(subject.get_dmx_value(DMX_UNIVERSE_SIZE).unwrap(), 2);
+    }
+
+    /// Asserts, that every frame written to the backend directly follows a break of at least
+    /// break_time and a mark after break of at least mark_after_break. Returns the number of
+    /// frames.
+    fn assert_break_timing(
+        backend: &MockBackend,
+        break_time: Duration,
+        mark_after_break: Duration,
+    ) -> usize {
+        let events = backend.line_events();
+        let mut frames = 0;
+        for (index, (written, event)) in events.iter().enumerate() {
+            if !matches!(event, MockLineEvent::Write(_)) {
+                continue;
+            }
+            assert!(index >= 2, "{:?}", events);
+            let (break_on, on) = &events[index - 2];
+            let (break_off, off) = &events[index - 1];
+            assert_eq!(
+                (on, off),
+                (&MockLineEvent::BreakOn, &MockLineEvent::BreakOff)
+            );
+            assert!(*break_off - *break_on >= break_time);
+            assert!(*written - *break_off >= mark_after_break);
+            frames += 1;
+        }
+        frames
+    }
+
+    #[test]
+    fn mock_break_timing_test() {
+        let break_time = Duration::from_micros(DMX_MIN_BREAK * 3);
+        let mark_after_break = Duration::from_micros(DMX_MIN_MAB * 10);
+        let timing = Timing {
+            break_time,
+            mark_after_break,
+            settle_delay: Duration::ZERO,
+            ..Timing::dmx_standard()
+        };
+
+        let backend = MockBackend::new();
+        let mut subject = OpenDmxBuilder::new(0)
+            .timing(timing)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        subject.reset().unwrap();
+        subject.write().unwrap();
+        subject.tick().unwrap();
+        assert_eq!(
+            assert_break_timing(&backend, break_time, mark_after_break),
+            2
+        );
+
+        // The run() thread sends exactly one timed break in front of every frame.
+        let (sender, receiver, snapshot) = subject.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        let start = Instant::now();
+        while snapshot.read().unwrap().frames() < 5 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+        while !backend.is_closed() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(assert_break_timing(&backend, break_time, mark_after_break) >= 7);
+    }
+
+    #[test]
+    fn mock_reset_retry_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+
+        // Setting the baud rate fails twice and succeeds with the third attempt.
+        backend.set_transient_failures(2);
+        subject.reset().unwrap();
+        assert_eq!(subject.connection_state(), ConnectionState::Ready);
+
+        backend.set_transient_failures(DEFAULT_RESET_ATTEMPTS as usize);
+        assert_eq!(
+            subject.reset(),
+            Err(DmxError::Ftdi(
+                "set baud rate",
+                libftd2xx::FtStatus::IO_ERROR
+            ))
+        );
+
+        let backend = MockBackend::new();
+        let mut subject = OpenDmxBuilder::new(0)
+            .reset_attempts(1)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        backend.set_transient_failures(1);
+        assert!(subject.reset().is_err());
+    }
+
+    #[test]
+    fn mock_timing_test() {
+        let subject = OpenDmxBuilder::new(0)
+            .timing(Timing::fast())
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        assert_eq!(subject.timing(), Timing::fast());
+        assert_eq!(subject.frame_interval(), Duration::from_micros(22727));
+
+        let mut timing = Timing::dmx_standard();
+        timing.break_time = Duration::from_micros(50);
+        assert_eq!(
+            OpenDmxBuilder::new(0)
+                .timing(timing)
+                .build_with_backend(Box::new(MockBackend::new()))
+                .unwrap_err(),
+            DmxError::InvalidTiming("break")
+        );
+    }
+
+    #[test]
+    fn mock_ftdi_mut_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        assert!(subject.ftdi_mut().is_none());
+    }
+
+    #[test]
+    fn mock_set_channel_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+
+        subject.set_channel(1, 10).unwrap();
+        subject.set_channel(DMX_UNIVERSE_SIZE, 20).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 10);
+        assert_eq!(subject.get_dmx_value(DMX_UNIVERSE_SIZE).unwrap(), 20);
+
+        assert_eq!(subject.set_channel(0, 1), Err(DmxError::InvalidChannel(0)));
+        assert_eq!(
+            subject.set_channel(DMX_UNIVERSE_SIZE + 1, 1),
+            Err(DmxError::InvalidChannel(DMX_UNIVERSE_SIZE + 1))
+        );
+        assert_eq!(subject.get_start_code(), NULL_START_CODE);
+    }
+
+    #[test]
+    fn mock_percent_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+
+        subject.set_dmx_percent(1, 100).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 255);
+        subject.set_dmx_percent(1, 200).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 255);
+        subject.set_dmx_percent(1, 50).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 128);
+        assert_eq!(subject.get_dmx_percent(1).unwrap(), 50);
+
+        // Every percentage survives the round trip.
+        for percent in 0..=100 {
+            subject.set_dmx_percent(1, percent).unwrap();
+            assert_eq!(subject.get_dmx_percent(1).unwrap(), percent);
+        }
+
+        assert_eq!(
+            subject.set_dmx_percent(DMX_SLOT_COUNT, 1),
+            Err(DmxError::InvalidChannel(DMX_SLOT_COUNT))
+        );
+    }
+
+    #[test]
+    fn mock_channel_const_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+
+        subject.set_channel_const::<1>(10);
+        subject.set_channel_const::<{ DMX_UNIVERSE_SIZE }>(20);
+        assert_eq!(subject.get_dmx_value(1), Ok(10));
+        assert_eq!(subject.get_channel_const::<1>(), 10);
+        assert_eq!(subject.get_channel_const::<{ DMX_UNIVERSE_SIZE }>(), 20);
+        // The start code is not a channel.
+        assert_eq!(subject.get_dmx_value(0), Ok(0));
+    }
+
+    #[test]
+    fn mock_set_dmx_value_clamped_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+
+        subject.set_dmx_value_clamped(1, 10);
+        subject.set_dmx_value_clamped(DMX_UNIVERSE_SIZE, 20);
+        assert_eq!(subject.get_dmx_value(1), Ok(10));
+        assert_eq!(subject.get_dmx_value(DMX_UNIVERSE_SIZE), Ok(20));
+
+        // Invalid channels are dropped.
+        subject.set_dmx_value_clamped(0, 30);
+        subject.set_dmx_value_clamped(DMX_UNIVERSE_SIZE + 1, 30);
+        assert_eq!(subject.get_dmx_value(0), Ok(0));
+        assert_eq!(subject.active_summary(), (2, DMX_UNIVERSE_SIZE));
+    }
+
+    #[test]
+    fn mock_bump_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        subject.set_dmx_value(1, 100).unwrap();
+
+        assert_eq!(subject.bump_dmx_value(1, 20), Ok(120));
+        assert_eq!(subject.bump_dmx_value(1, -30), Ok(90));
+        assert_eq!(subject.bump_dmx_value(1, 500), Ok(255));
+        assert_eq!(subject.bump_dmx_value(1, i16::MIN), Ok(0));
+        assert_eq!(subject.get_dmx_value(1), Ok(0));
+        assert_eq!(
+            subject.bump_dmx_value(DMX_SLOT_COUNT, 1),
+            Err(DmxError::InvalidChannel(DMX_SLOT_COUNT))
+        );
+
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        let (sender, receiver, snapshot) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // Relative changes add up.
+        sender.send(OpenDmxProtocol::Bump(1, 10)).unwrap();
+        sender.send(OpenDmxProtocol::Bump(1, 10)).unwrap();
+        sender.send(OpenDmxProtocol::Bump(1, -5)).unwrap();
+        let start = Instant::now();
+        while snapshot.read().unwrap().get_dmx_value(1) != Ok(15) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
+
+    #[test]
+    fn mock_f32_value_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+
+        subject.set_dmx_value_f32(1, 1.0).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 255);
+        assert_eq!(subject.get_dmx_value_f32(1).unwrap(), 1.0);
+
+        // Values are clamped ...
+        subject.set_dmx_value_f32(1, 2.0).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 255);
+        subject.set_dmx_value_f32(1, -1.0).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 0);
+
+        // ... and halves are rounded up: 0.5 * 255 = 127.5
+        subject.set_dmx_value_f32(1, 0.5).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 128);
+    }
+
+    #[test]
+    fn mock_replace_buffer_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        subject.reset().unwrap();
+        subject.set_start_code(TEXT_START_CODE);
+        subject.set_dmx_value(1, 1).unwrap();
+        subject.write().unwrap();
+        assert!(!subject.is_dirty());
+
+        let mut next = [0; DMX_UNIVERSE_SIZE];
+        next[0] = 2;
+        let previous = subject.replace_buffer(next);
+
+        assert_eq!(previous[0], 1);
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 2);
+        assert_eq!(subject.get_start_code(), TEXT_START_CODE);
+        assert!(subject.is_dirty());
+    }
+
+    #[test]
+    fn mock_connection_state_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        assert_eq!(subject.connection_state(), ConnectionState::Opened);
+
+        // The device has to be reset before it can transmit.
+        assert_eq!(subject.write(), Err(DmxError::NotConnected));
+        assert_eq!(subject.read(), Err(DmxError::NotConnected));
+        assert!(backend.writes().is_empty());
+
+        subject.reset().unwrap();
+        assert_eq!(subject.connection_state(), ConnectionState::Ready);
+        subject.write().unwrap();
+
+        subject.close().unwrap();
+        assert_eq!(subject.connection_state(), ConnectionState::Closed);
+        assert_eq!(subject.write(), Err(DmxError::NotConnected));
+        assert_eq!(subject.sync(), Err(DmxError::NotConnected));
+        assert_eq!(subject.reset(), Err(DmxError::NotConnected));
+    }
+
+    #[test]
+    fn mock_short_write_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        subject.set_dmx_value(1, 255).unwrap();
+
+        backend.set_write_limit(Some(100));
+        assert_eq!(subject.write(), Ok(100));
+        assert_eq!(backend.last_write().unwrap().len(), 100);
+        assert!(subject.is_dirty());
+
+        backend.set_write_limit(None);
+        assert_eq!(subject.write(), Ok(DMX_SLOT_COUNT));
+        assert!(!subject.is_dirty());
+    }
+
+    #[test]
+    fn mock_settle_delay_test() {
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+
+        let start = Instant::now();
+        let (sender, receiver, _) = device.spawn();
+        let started = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(started, OpenDmxProtocol::Started(_)));
+        assert!(start.elapsed() < DEFAULT_SETTLE_DELAY);
+
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
+
+    #[test]
+    fn mock_dropped_sender_test() {
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+
+        let (sender, receiver, _) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // Dropping the sender stops the thread and frees the device.
+        drop(sender);
+        let start = Instant::now();
+        while !backend.is_closed() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn mock_send_frames_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+
+        let frame = [255; DMX_UNIVERSE_SIZE];
+        subject.send_frames(&frame, 3).unwrap();
+
+        let writes = backend.writes();
+        assert_eq!(writes.len(), 3);
+        assert!(writes.iter().all(|w| w[1..] == frame));
+    }
+
+    #[test]
+    fn mock_format_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        subject.set_dmx_value(1, 255).unwrap();
+        subject.set_dmx_value(2, 10).unwrap();
+
+        assert_eq!(subject.to_string(), "Mock (), 2 of 512 channels set");
+
+        assert_eq!(subject.baud_rate(), 250000);
+        assert_eq!(subject.bits_per_word(), libftd2xx::BitsPerWord::Bits8);
+        assert_eq!(subject.stop_bits(), libftd2xx::StopBits::Bits2);
+        assert_eq!(subject.parity(), libftd2xx::Parity::No);
+
+        let debug = format!("{:?}", subject);
+        assert!(debug.contains("description: \"Mock\""));
+        assert!(debug.contains("non_zero_channels: 2"));
+        assert!(debug.contains("driver_version: Some(\"1.0.0\")"));
+        assert!(debug.contains("stop_bits: Bits2"));
+        assert!(debug.len() < 300);
+    }
+
+    #[test]
+    fn mock_line_error_test() {
+        let backend = MockBackend::new();
+        backend.set_event_status(LINE_STATUS_EVENT);
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+
+        let (sender, receiver, _) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let event = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        match event {
+            OpenDmxProtocol::LineError(status) => {
+                assert_eq!(status.event_status, LINE_STATUS_EVENT)
+            }
+            other => panic!("Expected a line error, got {:?}", other),
+        }
+
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
+
+    #[test]
+    fn mock_set_break_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.set_break(true).unwrap();
+        assert!(backend.is_break_on());
+
+        backend.set_unplugged(true);
+        let e = subject.set_break(false).unwrap_err();
+        assert_eq!(
+            e,
+            DmxError::Ftdi(
+                "set device break off",
+                libftd2xx::FtStatus::DEVICE_NOT_FOUND
+            )
+        );
+        assert_eq!(e.or_not_found(), DmxError::DeviceNotFound);
+    }
+
+    #[test]
+    fn mock_unplugged_test() {
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        let (_sender, receiver, _) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        backend.set_unplugged(true);
+        match receiver.recv_timeout(Duration::from_secs(5)) {
+            Ok(OpenDmxProtocol::Error(e)) => assert_eq!(*e, DmxError::DeviceNotFound),
+            other => panic!("Expected an error, got {:?}", other),
+        }
+        // The thread has stopped.
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_err());
+    }
+
+    #[test]
+    fn mock_device_not_found_test() {
+        let backend = MockBackend::new();
+        backend.set_unplugged(true);
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend))
+            .unwrap();
+
+        let (_sender, receiver, _) = device.spawn();
+        match receiver.recv_timeout(Duration::from_secs(5)) {
+            Ok(OpenDmxProtocol::Error(e)) => assert_eq!(*e, DmxError::DeviceNotFound),
+            other => panic!("Expected an error, got {:?}", other),
+        }
+        // The thread has stopped instead of panicking.
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_err());
+    }
+
+    #[test]
+    fn mock_mark_before_break_test() {
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .mark_before_break(Duration::from_millis(50))
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+
+        let (sender, receiver, snapshot) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        thread::sleep(Duration::from_millis(500));
+
+        // The mark is longer than the frame interval, so it limits the frame rate.
+        assert!(snapshot.read().unwrap().frames() <= 11);
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
+
+    #[test]
+    fn mock_apply_delta_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+
+        subject.apply_delta(&[(1, 10), (512, 20)]).unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 10);
+        assert_eq!(subject.get_dmx_value(512).unwrap(), 20);
+        assert!(subject.is_dirty());
+
+        let e = subject.apply_delta(&[(2, 30), (DMX_SLOT_COUNT, 40)]);
+        assert_eq!(e, Err(DmxError::InvalidChannel(DMX_SLOT_COUNT)));
+        assert_eq!(subject.get_dmx_value(2).unwrap(), 0);
+    }
+
+    #[test]
+    fn mock_soft_start_test() {
+        let mut subject = OpenDmxBuilder::new(0)
+            .soft_start(Some(Duration::from_secs(2)))
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        subject.set_dmx_value(1, 200).unwrap();
+
+        assert!(!subject.update_soft_start(Duration::ZERO));
+        assert_eq!(subject.output_frame()[1], 0);
+
+        assert!(!subject.update_soft_start(Duration::from_secs(1)));
+        assert_eq!(subject.output_frame()[1], 100);
+
+        assert!(subject.update_soft_start(Duration::from_secs(2)));
+        assert_eq!(subject.output_frame()[1], 200);
+    }
+
+    #[test]
+    fn mock_read_into_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        subject.set_dmx_value(1, 255).unwrap();
+
+        backend.push_rx(&[0, 1, 2]);
+        let mut received = [0; 2];
+        assert_eq!(subject.read_into(&mut received), Ok(2));
+        assert_eq!(received, [0, 1]);
+
+        backend.push_rx(&[3]);
+        assert_eq!(subject.read_into(&mut received), Ok(1));
+        assert_eq!(received, [3, 1]);
+
+        // The transmit buffer keeps its values.
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 255);
+    }
+
+    #[test]
+    fn mock_read_exact_timeout_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        assert_eq!(
+            subject.read_exact_timeout(1, Duration::ZERO),
+            Err(DmxError::NotConnected)
+        );
+        subject.reset().unwrap();
+
+        backend.push_rx(&[0, 1, 2]);
+        assert_eq!(
+            subject.read_exact_timeout(2, Duration::ZERO),
+            Ok(vec![0, 1])
+        );
+        assert_eq!(
+            subject.read_exact_timeout(2, Duration::from_millis(10)),
+            Err(DmxError::Timeout {
+                operation: "read device data",
+                actual: 1,
+                expected: 2,
+            })
+        );
+
+        // Waits for data arriving late.
+        let late = backend.clone();
+        let pusher = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            late.push_rx(&[3]);
+        });
+        assert_eq!(
+            subject.read_exact_timeout(2, Duration::from_secs(5)),
+            Ok(vec![2, 3])
+        );
+        pusher.join().unwrap();
+    }
+
+    #[test]
+    fn mock_buffer_eq_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        subject.set_start_code(TEXT_START_CODE);
+        assert!(subject.buffer_eq(&[0; DMX_UNIVERSE_SIZE]));
+
+        subject.set_dmx_value(2, 20).unwrap();
+        subject.set_dmx_value(3, 30).unwrap();
+        let mut expected = [0; DMX_UNIVERSE_SIZE];
+        expected[1] = 20;
+        expected[2] = 30;
+        assert!(subject.buffer_eq(&expected));
+        assert!(!subject.buffer_eq(&[0; DMX_UNIVERSE_SIZE]));
+
+        assert!(subject.buffer_matches_range(2, &[20, 30]));
+        assert!(subject.buffer_matches_range(1, &[0, 20]));
+        assert!(!subject.buffer_matches_range(3, &[20]));
+        assert!(!subject.buffer_matches_range(0, &[TEXT_START_CODE]));
+        assert!(!subject.buffer_matches_range(DMX_UNIVERSE_SIZE, &[0, 0]));
+        assert!(subject.buffer_matches_range(DMX_UNIVERSE_SIZE, &[0]));
+    }
+
+    #[test]
+    fn mock_flush_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+
+        backend.set_tx_queue(3);
+        assert_eq!(subject.flush(Duration::from_secs(5)), Ok(true));
+        assert_eq!(backend.tx_queue(), 0);
+
+        backend.set_tx_queue(u32::MAX);
+        assert_eq!(subject.flush(Duration::from_millis(10)), Ok(false));
+
+        // Drop drains the queue before closing the device.
+        backend.set_tx_queue(3);
+        drop(subject);
+        assert_eq!(backend.tx_queue(), 0);
+        assert!(backend.is_closed());
+    }
+
+    #[test]
+    fn mock_get_range_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        subject.set_dmx_value(2, 20).unwrap();
+        subject.set_dmx_value(3, 30).unwrap();
+
+        assert_eq!(subject.get_range(1, 3), Ok(vec![0, 20, 30]));
+        assert_eq!(subject.get_range(DMX_UNIVERSE_SIZE, 1), Ok(vec![0]));
+        assert_eq!(subject.get_range(2, 0), Ok(vec![]));
+        assert_eq!(subject.get_range(0, 1), Err(DmxError::InvalidChannel(0)));
+        assert_eq!(
+            subject.get_range(DMX_UNIVERSE_SIZE, 2),
+            Err(DmxError::InvalidChannel(DMX_UNIVERSE_SIZE + 1))
+        );
+        assert_eq!(
+            subject.get_range(2, usize::MAX),
+            Err(DmxError::InvalidChannel(DMX_UNIVERSE_SIZE + 1))
+        );
+
+        let mut values = [1; 2];
+        subject.get_range_into(2, &mut values).unwrap();
+        assert_eq!(values, [20, 30]);
+        let mut values = [1; 2];
+        assert!(subject
+            .get_range_into(DMX_UNIVERSE_SIZE, &mut values)
+            .is_err());
+        assert_eq!(values, [1; 2]);
+    }
+
+    #[test]
+    fn mock_on_frame_test() {
+        let mut device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        device.set_dmx_value(DMX_UNIVERSE_SIZE, 7).unwrap();
+
+        let (frames, observed) = mpsc::channel();
+        device.set_on_frame(Some(Box::new(move |frame| {
+            let _ = frames.send(frame[DMX_UNIVERSE_SIZE - 1]);
+        })));
+
+        let (sender, receiver, _) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(observed.recv_timeout(Duration::from_secs(5)), Ok(7));
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
 
-            let mut running = true;
-            let mut device = OpenDMX::new(id).unwrap();
-            thread::sleep(Duration::from_millis(1000));
+    #[test]
+    fn mock_observer_test() {
+        let mut device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        assert_eq!(
+            device.register_observer(0, Box::new(|_| {})),
+            Err(DmxError::InvalidChannel(0))
+        );
 
-            let granularity: TimerGranularity;
+        let (values, observed) = mpsc::channel();
+        device
+            .register_observer(
+                2,
+                Box::new(move |value| {
+                    let _ = values.send(value);
+                }),
+            )
+            .unwrap();
 
-            if now.elapsed().as_secs() > 3 {
-                granularity = TimerGranularity::Bad;
-            } else {
-                granularity = TimerGranularity::Good;
+        let (sender, receiver, _) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        sender.send(OpenDmxProtocol::SetValue(1, 50)).unwrap();
+        sender.send(OpenDmxProtocol::SetValue(2, 10)).unwrap();
+        assert_eq!(observed.recv_timeout(Duration::from_secs(5)), Ok(10));
+        sender.send(OpenDmxProtocol::SetValue(2, 200)).unwrap();
+        assert_eq!(observed.recv_timeout(Duration::from_secs(5)), Ok(200));
+
+        // Unchanged values are not reported again.
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+        while receiver.recv_timeout(Duration::from_secs(5)).is_ok() {}
+        assert!(observed.try_recv().is_err());
+    }
+
+    #[test]
+    fn mock_adaptive_wake_test() {
+        let mut device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .refresh_mode(RefreshMode::Adaptive {
+                idle_fps: 1,
+                active_fps: 40,
+                settle: Duration::from_millis(10),
+            })
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        let (values, observed) = mpsc::channel();
+        device
+            .register_observer(
+                1,
+                Box::new(move |value| {
+                    let _ = values.send(value);
+                }),
+            )
+            .unwrap();
+
+        let (sender, receiver, _) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        // Settle into the idle rate of one frame per second.
+        thread::sleep(Duration::from_millis(100));
+
+        sender.send(OpenDmxProtocol::SetValue(1, 5)).unwrap();
+        assert_eq!(observed.recv_timeout(Duration::from_millis(500)), Ok(5));
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
+
+    #[test]
+    fn mock_recent_frames_test() {
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .recent_frames(2)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        let (sender, receiver, snapshot) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        sender.send(OpenDmxProtocol::SetValue(1, 9)).unwrap();
+        let start = Instant::now();
+        while snapshot.read().unwrap().get_dmx_value(1).unwrap() != 9
+            || snapshot.read().unwrap().frames() < 3
+        {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        sender.send(OpenDmxProtocol::GetRecentFrames).unwrap();
+        match receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+            OpenDmxProtocol::RecentFrames(frames) => {
+                assert_eq!(frames.len(), 2);
+                assert_eq!(frames[1][0], 9);
             }
+            other => panic!("Expected recent frames, got {:?}", other),
+        }
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
 
-            device.reset().unwrap();
+    #[test]
+    fn mock_set_all_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        subject.set_start_code(TEXT_START_CODE);
 
-            // The DMX frame time duration in microseconds.
-            let frame_time: u128 =
-                (((1000.0 / (device.update_frequency / 1000) as f64) + 0.5).floor()) as u128;
-
-            while running {
-                // Receive all incomming commands and update our buffer
-                while let Ok(cmd) = receiver.try_recv() {
-                    match cmd {
-                        OpenDmxProtocol::SetValue(channel, value) => {
-                            match device.set_dmx_value(channel, value) {
-                                Ok(_) => {}
-                                Err(_) => {}
-                            }
-                        }
-                        OpenDmxProtocol::Stop => {
-                            running = false;
-                            continue;
-                        }
-                        OpenDmxProtocol::Reset => match device.reset() {
-                            Ok(_) => {}
-                            Err(_) => {
-                                println!("Error resetting a DMX-Device.")
-                            }
-                        },
-                        OpenDmxProtocol::ResetBuffer => {
-                            device.reset_buffer();
-                        }
-                        OpenDmxProtocol::ListDevices => {
-                            let mut payload = OpenDmxProtocol::DeviceList(Vec::new());
-                            if let Ok(list) = Self::list_devices() {
-                                payload = OpenDmxProtocol::DeviceList(list);
-                            }
+        subject.set_all(128);
+        assert_eq!(subject.get_start_code(), TEXT_START_CODE);
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 128);
+        assert_eq!(subject.get_dmx_value(DMX_UNIVERSE_SIZE).unwrap(), 128);
+        assert!(subject.is_dirty());
+    }
 
-                            match sender2.send(payload) {
-                                Ok(_) => {}
-                                Err(_) => {
-                                    println!("Could not send a list devices response.")
-                                }
-                            }
-                        }
-                        OpenDmxProtocol::DeviceList(_device_infos) => {}
-                    }
-                }
+    #[test]
+    fn mock_layer_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        subject.set_dmx_value(1, 100).unwrap();
 
-                // Update device.
-                now = Instant::now();
-                if !device.set_break(true) {
-                    Self::framesleep(&now, frame_time, granularity);
-                    continue;
-                }
+        subject.set_layer_value(0, 1, 50).unwrap();
+        subject.set_layer_value(1, 2, 200).unwrap();
+        subject.write().unwrap();
+        let frame = backend.last_write().unwrap();
+        assert_eq!(&frame[1..3], &[100, 200]);
 
-                if granularity == TimerGranularity::Good {
-                    thread::sleep(Duration::from_micros(DMX_BREAK));
-                }
+        subject.set_layer_mode(0, MergeMode::Ltp);
+        subject.write().unwrap();
+        assert_eq!(backend.last_write().unwrap()[1], 50);
 
-                if !device.set_break(false) {
-                    Self::framesleep(&now, frame_time, granularity);
-                    continue;
-                }
+        // The layers do not change the buffer.
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 100);
+        subject.clear_layer(0);
+        subject.write().unwrap();
+        assert_eq!(backend.last_write().unwrap()[1], 100);
+    }
 
-                if granularity == TimerGranularity::Good {
-                    thread::sleep(Duration::from_micros(DMX_MAB));
-                }
+    #[test]
+    fn mock_bounded_test() {
+        let device = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
 
-                match device.write() {
-                    Ok(_) => {
-                        Self::framesleep(&now, frame_time, granularity);
-                    }
+        // The thread does not handle commands during the settle delay.
+        let (sender, _receiver, _) = device.spawn_bounded(1);
+        sender.try_send(OpenDmxProtocol::SetValue(1, 1)).unwrap();
+        assert!(matches!(
+            sender.try_send(OpenDmxProtocol::SetValue(1, 2)),
+            Err(mpsc::TrySendError::Full(_))
+        ));
+    }
 
-                    Err(_) => {
-                        Self::framesleep(&now, frame_time, granularity);
-                    }
-                }
+    #[test]
+    fn mock_active_summary_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        subject.set_start_code(TEXT_START_CODE);
+        assert_eq!(subject.active_summary(), (0, 0));
+
+        subject.set_dmx_value(3, 1).unwrap();
+        subject.set_dmx_value(210, 255).unwrap();
+        assert_eq!(subject.active_summary(), (2, 210));
+    }
+
+    #[test]
+    fn mock_cue_list_test() {
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        let (sender, receiver, snapshot) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let mut list = CueList::new();
+        list.push(
+            [10; DMX_UNIVERSE_SIZE],
+            Duration::ZERO,
+            Duration::from_millis(50),
+        );
+        list.push(
+            [20; DMX_UNIVERSE_SIZE],
+            Duration::ZERO,
+            Duration::from_millis(50),
+        );
+        sender.send(OpenDmxProtocol::PlayCues(list)).unwrap();
+
+        for expected in 0..2 {
+            match receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+                OpenDmxProtocol::CueStarted(index) => assert_eq!(index, expected),
+                other => panic!("Expected a cue, got {:?}", other),
             }
-        });
+        }
 
-        (sender, receiver2)
+        let start = Instant::now();
+        while snapshot.read().unwrap().get_dmx_value(1) != Ok(20) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        sender.send(OpenDmxProtocol::Stop).unwrap();
     }
-}
 
-/// A device must be closed once it´s not used anymore. If not, the device will be blocked.
-impl Drop for OpenDMX {
-    fn drop(&mut self) {
-        self.reset_buffer();
+    #[test]
+    fn mock_latch_frames_test() {
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .latch_frames(3)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        let (sender, receiver, snapshot) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
 
-        match self.write() {
-            Ok(_) => {}
-            Err(e) => {
-                println!("Could not reset device. Error: {}", e);
+        sender.send(OpenDmxProtocol::SetValue(1, 10)).unwrap();
+        let start = Instant::now();
+        while snapshot.read().unwrap().get_dmx_value(1) != Ok(10) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // Delayed until the previous value has been transmitted three times.
+        sender.send(OpenDmxProtocol::SetValue(1, 20)).unwrap();
+        while snapshot.read().unwrap().get_dmx_value(1) != Ok(20) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(1));
+        }
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_err());
+
+        let values: Vec<u8> = backend.writes().iter().map(|w| w[1]).collect();
+        assert!(values.iter().filter(|v| **v == 10).count() >= 3);
+    }
+
+    #[test]
+    fn mock_on_control_loss_test() {
+        let mut idle = [0; DMX_UNIVERSE_SIZE];
+        idle[0] = 30;
+        let idle = ResetMode::Idle(Box::new(idle));
+        for (mode, reset_mode, expected) in [
+            (OnControlLoss::Hold, ResetMode::Zero, 10),
+            (OnControlLoss::Blackout, ResetMode::Zero, 0),
+            // The blackout keeps the idle state of the rig.
+            (OnControlLoss::Blackout, idle, 30),
+        ] {
+            let backend = MockBackend::new();
+            let device = OpenDmxBuilder::new(0)
+                .settle_delay(Duration::ZERO)
+                .on_control_loss(mode)
+                .reset_mode(reset_mode)
+                .build_with_backend(Box::new(backend.clone()))
+                .unwrap();
+            let (sender, receiver, snapshot) = device.spawn();
+            receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+            sender.send(OpenDmxProtocol::SetValue(1, 10)).unwrap();
+            drop(sender);
+
+            // The thread keeps transmitting.
+            let start = Instant::now();
+            while snapshot.read().unwrap().get_dmx_value(1) != Ok(expected)
+                || snapshot.read().unwrap().frames() < 20
+            {
+                assert!(start.elapsed() < Duration::from_secs(5));
+                thread::sleep(Duration::from_millis(10));
             }
+            assert!(!backend.is_closed());
         }
 
-        match self.close() {
-            Ok(_) => {}
-            Err(e) => {
-                println!("Could not close open_dmx device. Error: {}", e);
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        let (sender, receiver, _) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        drop(sender);
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_err());
+        let start = Instant::now();
+        while !backend.is_closed() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn mock_stop_hold_test() {
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        let (sender, receiver, snapshot) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        sender.send(OpenDmxProtocol::SetValue(1, 10)).unwrap();
+        let start = Instant::now();
+        while snapshot.read().unwrap().get_dmx_value(1) != Ok(10) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        sender.send(OpenDmxProtocol::StopHold).unwrap();
+        while !backend.is_closed() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        // The last frame is the look, not a blackout.
+        assert_eq!(backend.last_write().unwrap()[1], 10);
+    }
+
+    #[test]
+    fn mock_reset_mode_test() {
+        let backend = MockBackend::new();
+        let mut idle = [0; DMX_UNIVERSE_SIZE];
+        idle[1] = 255;
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        assert_eq!(subject.reset_mode(), &ResetMode::Zero);
+
+        subject.set_idle_state(idle);
+        subject.set_all(10);
+        subject.reset_buffer();
+        assert!(subject.buffer_eq(&idle));
+
+        // The frame on drop is the idle state, too.
+        subject.set_all(10);
+        drop(subject);
+        let frame = backend.last_write().unwrap();
+        assert_eq!(frame[0], NULL_START_CODE);
+        assert_eq!(&frame[1..4], &[0, 255, 0]);
+    }
+
+    #[test]
+    fn mock_reset_to_idle_test() {
+        let mut idle = [0; DMX_UNIVERSE_SIZE];
+        idle[0] = 100;
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .reset_mode(ResetMode::Idle(Box::new(idle)))
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        let (sender, receiver, snapshot) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        sender.send(OpenDmxProtocol::SetValue(2, 10)).unwrap();
+        sender
+            .send(OpenDmxProtocol::SetLayerValue(1, 3, 20))
+            .unwrap();
+        sender
+            .send(OpenDmxProtocol::TestPattern(TestPattern::AllOn))
+            .unwrap();
+        sender.send(OpenDmxProtocol::ResetToIdle).unwrap();
+        let start = Instant::now();
+        loop {
+            if snapshot.read().unwrap().values()[1..4] == [100, 0, 0] {
+                break;
             }
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
         }
+
+        sender.send(OpenDmxProtocol::Stop).unwrap();
     }
-}
 
-/// Tests cannot run in parallel, because in most cases we got only one device and
-/// this library needs exclusive access to the device.
-///
-/// Run tests with:
-/// cargo test -- --nocapture --test-threads=1
-#[cfg(test)]
-mod tests {
-    use libftd2xx::DeviceType;
+    #[test]
+    fn mock_event_log_test() {
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        let (sender, receiver, _) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
 
-    use super::*;
+        sender.send(OpenDmxProtocol::Pause).unwrap();
+        sender.send(OpenDmxProtocol::Resume).unwrap();
+        sender.send(OpenDmxProtocol::Reset).unwrap();
+        sender.send(OpenDmxProtocol::StartCode(0x17)).unwrap();
+        sender
+            .send(OpenDmxProtocol::FrameSize(FrameSize::Fixed(24)))
+            .unwrap();
+        sender.send(OpenDmxProtocol::GetEventLog).unwrap();
+        let events = loop {
+            match receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+                OpenDmxProtocol::EventLog(events) => break events,
+                OpenDmxProtocol::Paused | OpenDmxProtocol::Resumed => {}
+                other => panic!("Expected an event log, got {:?}", other),
+            }
+        };
+
+        let kinds: Vec<EventKind> = events.iter().map(|(_, kind)| *kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::Started,
+                EventKind::Paused,
+                EventKind::Resumed,
+                EventKind::Reset,
+                EventKind::StartCodeChanged(0x17),
+                EventKind::FrameSizeChanged(FrameSize::Fixed(24))
+            ]
+        );
+        assert!(events.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
 
     #[test]
-    fn num_devices_test() {
-        let subject = OpenDMX::get_num_of_devices().unwrap();
-        assert_eq!(subject, 1);
+    fn mock_stats_test() {
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        let (sender, receiver, _) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // One second at the default 40 frames per second.
+        let start = Instant::now();
+        while backend.writes().len() <= 40 && start.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(1));
+        }
+        let before = backend.writes().len() as u64;
+        sender.send(OpenDmxProtocol::GetStats).unwrap();
+        let stats = match receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+            OpenDmxProtocol::Stats(stats) => stats,
+            other => panic!("Expected stats, got {:?}", other),
+        };
+        let after = backend.writes().len() as u64;
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+        assert!(before > 40, "Only {} frames within 10s", before);
+
+        // Every frame written to the backend is counted.
+        assert!((before..=after).contains(&stats.frames), "{:?}", stats);
+        assert_eq!(stats.failed_frames, 0);
+
+        // Frames never start early, so the second after the first frame holds at most 41 frames,
+        // measured on the time stamps of the mock, and one more for a delayed first write. A
+        // busy machine may delay frames, so there is no lower limit.
+        let writes: Vec<Instant> = backend
+            .line_events()
+            .into_iter()
+            .filter(|(_, event)| matches!(event, MockLineEvent::Write(_)))
+            .map(|(at, _)| at)
+            .collect();
+        let second = writes
+            .iter()
+            .filter(|at| **at - writes[0] < Duration::from_secs(1))
+            .count();
+        assert!(second <= 42, "{} frames within one second", second);
     }
 
     #[test]
-    fn local_buffer_test() {
-        let mut subject = OpenDMX::new(0).unwrap();
-        // Check default
-        assert_eq!(subject.get_dmx_value(0).unwrap(), 0);
+    fn mock_idle_test() {
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        let (sender, receiver, snapshot) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
 
-        // Set a value...
-        subject.set_dmx_value(0, 1).unwrap();
-        assert_eq!(subject.get_dmx_value(0).unwrap(), 1);
+        sender.send(OpenDmxProtocol::Idle).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        let writes = backend.writes().len();
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(backend.writes().len(), writes);
+        assert!(!backend.is_closed());
 
-        // ... overwrite the value again.
-        subject.set_dmx_value(0, 0).unwrap();
-        assert_eq!(subject.get_dmx_value(0).unwrap(), 0);
+        // Commands are still handled.
+        sender.send(OpenDmxProtocol::SetValue(1, 10)).unwrap();
+        sender.send(OpenDmxProtocol::Engage).unwrap();
+        let start = Instant::now();
+        while snapshot.read().unwrap().get_dmx_value(1) != Ok(10) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
 
-        // Test invalid channel numbers.
-        let e = subject.set_dmx_value(BUFFER_SIZE, 10);
-        assert_eq!(e, Err("Invalid channel number".to_owned()));
+    #[test]
+    fn mock_test_pattern_test() {
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(MockBackend::new()))
+            .unwrap();
+        let (sender, receiver, snapshot) = device.spawn();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let wait_for = |channel: usize, value: u8| {
+            let start = Instant::now();
+            while snapshot.read().unwrap().get_dmx_value(channel) != Ok(value) {
+                assert!(start.elapsed() < Duration::from_secs(5));
+                thread::sleep(Duration::from_millis(10));
+            }
+        };
+
+        sender.send(OpenDmxProtocol::SetValue(2, 10)).unwrap();
+        wait_for(2, 10);
+
+        sender
+            .send(OpenDmxProtocol::TestPattern(TestPattern::Checkerboard))
+            .unwrap();
+        wait_for(1, 255);
+        assert_eq!(snapshot.read().unwrap().get_dmx_value(2), Ok(0));
+
+        sender.send(OpenDmxProtocol::StopTestPattern).unwrap();
+        wait_for(2, 10);
+        assert_eq!(snapshot.read().unwrap().get_dmx_value(1), Ok(0));
+
+        sender.send(OpenDmxProtocol::Stop).unwrap();
+    }
+
+    #[test]
+    fn mock_checked_reset_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.checked_reset().unwrap();
+
+        backend.set_ignore_latency_timer(true);
+        subject.latency_timer = Duration::from_millis(5);
+        assert_eq!(
+            subject.checked_reset(),
+            Err(DmxError::ConfigMismatch("latency timer"))
+        );
+    }
+
+    #[test]
+    fn mock_apply_snapshot_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        let mut snapshot = UniverseSnapshot::default();
+        let mut frame = [0; DMX_SLOT_COUNT];
+        frame[0] = TEXT_START_CODE;
+        frame[DMX_UNIVERSE_SIZE] = 3;
+        snapshot.update(&frame);
+
+        subject.apply_snapshot(&snapshot);
+        assert_eq!(subject.get_dmx_value(DMX_UNIVERSE_SIZE).unwrap(), 3);
+        assert_eq!(subject.get_start_code(), NULL_START_CODE);
+    }
+
+    #[test]
+    fn mock_output_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        let mut universe = Universe::new();
+        universe.set(DMX_UNIVERSE_SIZE, 10).unwrap();
+
+        subject.output(&universe);
+        assert_eq!(subject.get_dmx_value(DMX_UNIVERSE_SIZE).unwrap(), 10);
+        assert!(subject.is_dirty());
+    }
+
+    #[test]
+    fn mock_load_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+        subject.set_start_code(TEXT_START_CODE);
+        let mut scene = [0; DMX_UNIVERSE_SIZE];
+        scene[0] = 10;
+        scene[DMX_UNIVERSE_SIZE - 1] = 20;
+
+        subject.load(scene);
+        assert!(subject.buffer_eq(&scene));
+        assert!(subject.is_dirty());
+        assert_eq!(subject.get_dmx_value(0), Ok(TEXT_START_CODE));
+        assert_eq!(Universe::from(scene).get(DMX_UNIVERSE_SIZE), Ok(20));
+    }
+
+    #[test]
+    fn mock_frame_size_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        assert_eq!(subject.frame_size(), FrameSize::Full512);
+
+        subject.set_frame_size(FrameSize::Fixed(24)).unwrap();
+        subject.write().unwrap();
+        assert_eq!(backend.last_write().unwrap().len(), 25);
+
+        subject.set_frame_size(FrameSize::Minimal).unwrap();
+        subject.set_dmx_value(100, 1).unwrap();
+        subject.write().unwrap();
+        assert_eq!(backend.last_write().unwrap().len(), 101);
+        assert!(!subject.is_dirty());
+
+        assert_eq!(
+            subject.set_frame_size(FrameSize::Fixed(0)),
+            Err(DmxError::InvalidFrameSize(0))
+        );
+        assert_eq!(subject.frame_size(), FrameSize::Minimal);
+        assert!(OpenDmxBuilder::new(0)
+            .frame_size(FrameSize::Fixed(513))
+            .build_with_backend(Box::new(MockBackend::new()))
+            .is_err());
+    }
+
+    #[test]
+    fn device_watcher_test() {
+        let info = OpenDmxDeviceInfo {
+            serial: String::from("A1"),
+            description: String::from("dongle"),
+            device_type: String::from("FT232R"),
+            vendor_id: 0x0403,
+            product_id: 0x6001,
+        };
+        let list = vec![info];
+        let polled = list.clone();
+        let (receiver, handle) =
+            OpenDMX::spawn_device_watcher(Duration::from_millis(1), move || Ok(polled.clone()));
+
+        assert_eq!(receiver.recv().unwrap(), list);
+        assert_eq!(receiver.recv().unwrap(), list);
+
+        // The unchanged list notices the dropped receiver, so the thread ends.
+        drop(receiver);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn mock_max_channels_test() {
+        let build = |channels| {
+            OpenDmxBuilder::new(0)
+                .max_channels(channels)
+                .build_with_backend(Box::new(MockBackend::new()))
+        };
+        assert!(matches!(
+            build(DMX_UNIVERSE_SIZE - 1),
+            Err(DmxError::InvalidMaxChannels(511))
+        ));
+        assert!(build(DMX_MAX_CHANNELS + 1).is_err());
+
+        let backend = MockBackend::new();
+        let mut subject = OpenDmxBuilder::new(0)
+            .max_channels(1024)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        subject.reset().unwrap();
+        assert_eq!(subject.channel_count(), 1024);
+
+        subject.set_dmx_value(1024, 7).unwrap();
+        subject.set_dmx_value(512, 9).unwrap();
+        assert_eq!(subject.get_dmx_value(1024), Ok(7));
+        assert_eq!(subject.get_range(1023, 2), Ok(vec![0, 7]));
+        assert_eq!(
+            subject.set_dmx_value(1025, 1),
+            Err(DmxError::InvalidChannel(1025))
+        );
+
+        // Frames still carry the standard universe only.
+        subject.write().unwrap();
+        let frame = backend.last_write().unwrap();
+        assert_eq!(frame.len(), DMX_SLOT_COUNT);
+        assert_eq!(frame[512], 9);
+
+        subject.reset_buffer();
+        assert_eq!(subject.get_dmx_value(1024), Ok(0));
+    }
+
+    #[test]
+    fn mock_tick_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        subject.reset().unwrap();
+        subject.set_dmx_value(1, 255).unwrap();
 
-        let e2 = subject.get_dmx_value(BUFFER_SIZE);
-        assert_eq!(e2, Err("Invalid channel number".to_owned()));
+        subject.tick().unwrap();
+        assert_eq!(backend.writes().len(), 1);
+        assert_eq!(backend.last_write().unwrap()[1], 255);
+        assert!(!backend.is_break_on());
+        assert!(!subject.is_dirty());
     }
 
     #[test]
@@ -539,6 +3909,46 @@ mod tests {
         let _subject2 = OpenDMX::new(0).unwrap();
     }
 
+    #[test]
+    fn with_index_retry_test() {
+        let _subject =
+            OpenDMX::with_index_retry(0, Duration::from_secs(1), Duration::from_millis(100))
+                .unwrap();
+
+        // The device is already open, so every attempt fails.
+        let start = Instant::now();
+        assert!(OpenDMX::with_index_retry(
+            0,
+            Duration::from_millis(300),
+            Duration::from_millis(100)
+        )
+        .is_err());
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn ftdi_mut_test() {
+        let mut subject = OpenDMX::new(0).unwrap();
+        assert!(subject.ftdi_mut().is_some());
+    }
+
+    #[test]
+    fn enumerate_test() {
+        let subject = OpenDMX::enumerate().unwrap();
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject[0].index, 0);
+        assert!(!subject[0].busy);
+    }
+
+    #[test]
+    fn is_in_use_test() {
+        assert!(!OpenDMX::is_in_use(0));
+        assert!(!OpenDMX::is_in_use(-1));
+
+        let _subject = OpenDMX::new(0).unwrap();
+        assert!(OpenDMX::is_in_use(0));
+    }
+
     /// This test might fail with different types of open_dmx hardware.
     #[test]
     pub fn device_info_test() {
@@ -549,21 +3959,22 @@ mod tests {
         let info = subject.get_device_info();
         assert_eq!("FT232R USB UART".to_owned(), info.description);
         assert_eq!("AL05O9B5".to_owned(), info.serial_number);
-        assert_eq!(DeviceType::FT232R, info.device_type);       // This is hardware specific!
+        assert_eq!(DeviceType::FT232R, info.device_type); // This is hardware specific!
     }
 
     /// This test might fail with different types of open_dmx hardware.
     #[test]
     pub fn async_list_devices() {
-        let (sender, receiver) = OpenDMX::run(0);
+        let (sender, receiver, _) = OpenDMX::run(0);
         sender.send(OpenDmxProtocol::ListDevices).unwrap();
         while let Ok(cmd) = receiver.try_recv() {
             match cmd {
                 OpenDmxProtocol::DeviceList(device_infos) => {
                     assert!(device_infos.len() == 1);
                     assert!(device_infos[0].port_open);
-                    assert_eq!(device_infos[0].device_type, DeviceType::FT232R);    // This is hardware specific!
-                },
+                    assert_eq!(device_infos[0].device_type, DeviceType::FT232R);
+                    // This is hardware specific!
+                }
                 _ => {
                     panic!("Expected a device list only.")
                 }
@@ -591,6 +4002,43 @@ mod tests {
         assert_eq!(0, status.event_status);
     }
 
+    #[test]
+    pub fn purge_test() {
+        let mut subject = OpenDMX::new(0).unwrap();
+        // Open device
+        subject.reset().unwrap();
+
+        subject.purge().unwrap();
+
+        // After purging both queues have to be empty.
+        let status = subject.get_device_status().unwrap();
+        assert_eq!(0, status.ammount_in_rx_queue);
+        assert_eq!(0, status.ammount_in_tx_queue);
+    }
+
+    #[test]
+    pub fn latency_timer_test() {
+        let mut subject = OpenDMX::builder(0)
+            .latency_timer(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        // The latency timer is applied on reset.
+        subject.reset().unwrap();
+
+        let timer = subject.backend.latency_timer().unwrap();
+        assert_eq!(Duration::from_millis(1), timer);
+    }
+
+    #[test]
+    pub fn invalid_usb_transfer_size_test() {
+        // Sizes are validated before the device is opened.
+        let e = OpenDMX::builder(0).usb_transfer_size(Some(100)).build();
+        assert_eq!(e.err(), Some(DmxError::InvalidTransferSize(100)));
+
+        let e2 = OpenDMX::builder(0).usb_transfer_size(Some(0)).build();
+        assert_eq!(e2.err(), Some(DmxError::InvalidTransferSize(0)));
+    }
+
     #[test]
     pub fn write_data_test() {
         let mut subject = OpenDMX::new(0).unwrap();
@@ -621,14 +4069,14 @@ mod tests {
     pub fn run_test() {
         let sender = OpenDMX::run(0);
 
-        match sender.0.send(OpenDmxProtocol::SetValue(2, 5 as u8)) {
+        match sender.0.send(OpenDmxProtocol::SetValue(2, 5_u8)) {
             Ok(_) => {}
             Err(e) => {
                 println!("Could not send data: {:?}", e);
             }
         }
 
-        match sender.0.send(OpenDmxProtocol::SetValue(3, 5 as u8)) {
+        match sender.0.send(OpenDmxProtocol::SetValue(3, 5_u8)) {
             Ok(_) => {}
             Err(e) => {
                 println!("Could not send data: {:?}", e);
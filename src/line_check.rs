@@ -0,0 +1,101 @@
+use crate::{DmxError, OpenDMX, DMX_SLOT_COUNT, LINE_STATUS_EVENT, TEST_START_CODE};
+use std::thread;
+
+/// Number of test frames transmitted by OpenDMX::line_check().
+const LINE_CHECK_FRAMES: usize = 20;
+
+/// Result of OpenDMX::line_check().
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineQuality {
+    /// All test frames have been transmitted without errors.
+    Good,
+    /// A few test frames failed. Check connectors and the terminator.
+    Suspect,
+    /// More than a quarter of the test frames failed, or the device is not ready.
+    Bad,
+}
+
+impl OpenDMX {
+    /// Transmit a few test packets and classify the line by the errors reported by the device.
+    /// Interfaces, that receive their own output, report framing errors and overruns caused by
+    /// reflections or a missing terminator. Transmit only interfaces still report failed or short
+    /// USB transfers.
+    ///
+    /// The test packets use TEST_START_CODE, so fixtures ignore them. The buffer is not changed.
+    /// Do not call this while the device is used by run().
+    pub fn line_check(&mut self) -> LineQuality {
+        if self.ensure_ready().is_err() || self.purge().is_err() {
+            return LineQuality::Bad;
+        }
+
+        let mut frame = [0x55; DMX_SLOT_COUNT];
+        frame[0] = TEST_START_CODE;
+
+        let mut errors = 0;
+        for _ in 0..LINE_CHECK_FRAMES {
+            let failed = match self.send_test_frame(&frame) {
+                Ok(()) => match self.get_device_status() {
+                    Ok(status) => status.event_status & LINE_STATUS_EVENT != 0,
+                    Err(_) => true,
+                },
+                Err(_) => true,
+            };
+
+            if failed {
+                errors += 1;
+            }
+        }
+
+        if errors == 0 {
+            LineQuality::Good
+        } else if errors * 4 <= LINE_CHECK_FRAMES {
+            LineQuality::Suspect
+        } else {
+            LineQuality::Bad
+        }
+    }
+
+    fn send_test_frame(&mut self, frame: &[u8; DMX_SLOT_COUNT]) -> Result<(), DmxError> {
+        self.set_break(true)?;
+        thread::sleep(self.break_time);
+        self.set_break(false)?;
+        thread::sleep(self.mark_after_break);
+
+        match self.backend.write(frame) {
+            Ok(written) if written == frame.len() => Ok(()),
+            Ok(written) => Err(DmxError::Timeout {
+                operation: "write test packet",
+                actual: written,
+                expected: frame.len(),
+            }),
+            Err(e) => Err(DmxError::Ftdi("write test packet", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MockBackend;
+
+    use super::*;
+
+    #[test]
+    fn mock_line_check_test() {
+        let backend = MockBackend::new();
+        let mut subject = OpenDMX::new_with_backend(backend.clone()).unwrap();
+        assert_eq!(subject.line_check(), LineQuality::Bad);
+
+        subject.reset().unwrap();
+        assert_eq!(subject.line_check(), LineQuality::Good);
+        assert_eq!(backend.writes().len(), LINE_CHECK_FRAMES);
+        assert_eq!(backend.last_write().unwrap()[0], TEST_START_CODE);
+        assert_eq!(subject.get_start_code(), 0);
+
+        backend.set_event_status(LINE_STATUS_EVENT);
+        assert_eq!(subject.line_check(), LineQuality::Bad);
+
+        backend.set_event_status(0);
+        backend.set_write_limit(Some(100));
+        assert_eq!(subject.line_check(), LineQuality::Bad);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    DmxError, OpenDMX, OpenDmxDeviceInfo, OpenDmxHandle, OpenDmxProtocol, SharedSnapshot,
+    DMX_UNIVERSE_SIZE,
+};
+use std::{sync::mpsc::Receiver, time::Instant};
+
+/// A device running in a background thread, that is owned by an OpenDmxManager.
+pub struct RunningDevice {
+    pub info: OpenDmxDeviceInfo,
+    pub handle: OpenDmxHandle,
+    pub receiver: Receiver<OpenDmxProtocol>,
+    pub snapshot: SharedSnapshot,
+}
+
+/// Runs several devices at once, each in its own background thread (see OpenDMX::spawn()).
+/// All devices share one clock, so devices with the same update frequency transmit their frames
+/// in lockstep (see OpenDmxProtocol::SyncClock). All devices are stopped when the manager is
+/// dropped.
+pub struct OpenDmxManager {
+    devices: Vec<RunningDevice>,
+}
+
+impl OpenDmxManager {
+    /// Start a background thread for each of the given devices.
+    pub fn new<I: IntoIterator<Item = OpenDMX>>(devices: I) -> Self {
+        let epoch = Instant::now();
+        let devices = devices
+            .into_iter()
+            .map(|device| {
+                let info = device.device_info_owned();
+                let (sender, receiver, snapshot) = device.spawn();
+                // Commands are handled in order, so the clock is set before the first frame.
+                if sender.send(OpenDmxProtocol::SyncClock(epoch)).is_err() {
+                    log::debug!("Could not sync dmx device. The device thread is not running");
+                }
+                RunningDevice {
+                    info,
+                    handle: OpenDmxHandle::new(sender),
+                    receiver,
+                    snapshot,
+                }
+            })
+            .collect();
+
+        OpenDmxManager { devices }
+    }
+
+    /// Open all connected devices (see OpenDMX::open_all()) and start a background thread for
+    /// each device, that could be opened. Returns the errors of all other devices.
+    pub fn open_all() -> (Self, Vec<DmxError>) {
+        let mut devices = Vec::new();
+        let mut errors = Vec::new();
+        for result in OpenDMX::open_all() {
+            match result {
+                Ok(device) => devices.push(device),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (Self::new(devices), errors)
+    }
+
+    /// All running devices in the order they have been started.
+    pub fn devices(&self) -> &[RunningDevice] {
+        &self.devices
+    }
+
+    /// The handle of the device at the given index.
+    pub fn handle(&self, index: usize) -> Option<&OpenDmxHandle> {
+        self.devices.get(index).map(|d| &d.handle)
+    }
+
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    /// Apply the given looks with the first frame at or after the given point in time, looks[0]
+    /// to the first device and so on. All devices apply their looks with the same frame. Devices
+    /// without a look keep their values, looks without a device are ignored. Returns the first
+    /// error, the looks are sent to all other devices nevertheless.
+    pub fn schedule_at(
+        &self,
+        at: Instant,
+        looks: &[[u8; DMX_UNIVERSE_SIZE]],
+    ) -> Result<(), DmxError> {
+        let mut result = Ok(());
+        for (device, look) in self.devices.iter().zip(looks) {
+            if let Err(e) = device.handle.schedule_frame(at, look) {
+                result = result.and(Err(e));
+            }
+        }
+        result
+    }
+
+    /// Stop all background threads. Devices, that already stopped, are ignored.
+    pub fn stop_all(&self) {
+        for device in &self.devices {
+            let _ = device.handle.stop();
+        }
+    }
+}
+
+impl Drop for OpenDmxManager {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MockBackend, MockLineEvent, OpenDmxBuilder};
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+
+    /// Time of the first frame written to the backend with the given value on channel 1.
+    fn first_write(backend: &MockBackend, value: u8) -> Option<Instant> {
+        let times = backend
+            .line_events()
+            .into_iter()
+            .filter(|(_, event)| matches!(event, MockLineEvent::Write(_)))
+            .map(|(at, _)| at);
+        backend
+            .writes()
+            .into_iter()
+            .zip(times)
+            .find(|(frame, _)| frame[1] == value)
+            .map(|(_, at)| at)
+    }
+
+    #[test]
+    fn mock_schedule_at_test() {
+        let backends = [MockBackend::new(), MockBackend::new()];
+        let devices: Vec<OpenDMX> = backends
+            .iter()
+            .map(|backend| {
+                OpenDmxBuilder::new(0)
+                    .settle_delay(Duration::ZERO)
+                    .build_with_backend(Box::new(backend.clone()))
+                    .unwrap()
+            })
+            .collect();
+        let frame_interval = devices[0].frame_interval();
+        let subject = OpenDmxManager::new(devices);
+
+        let at = Instant::now() + Duration::from_millis(200);
+        subject
+            .schedule_at(at, &[[10; DMX_UNIVERSE_SIZE], [20; DMX_UNIVERSE_SIZE]])
+            .unwrap();
+
+        let mut switched = Vec::new();
+        for (backend, expected) in backends.iter().zip([10, 20]) {
+            loop {
+                if let Some(written) = first_write(backend, expected) {
+                    switched.push(written);
+                    break;
+                }
+                assert!(Instant::now() < at + Duration::from_secs(5));
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        // Frames are aligned to the closest tick of the shared clock, so the look may appear
+        // half a frame early, but never earlier.
+        for written in &switched {
+            assert!(*written + frame_interval / 2 >= at, "{:?}", switched);
+        }
+        // Both universes switch with the same frame.
+        let apart = if switched[0] > switched[1] {
+            switched[0] - switched[1]
+        } else {
+            switched[1] - switched[0]
+        };
+        assert!(apart < frame_interval, "{:?} apart", apart);
+    }
+
+    #[test]
+    fn mock_manager_test() {
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        let subject = OpenDmxManager::new([device]);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.devices()[0].info.description, "Mock");
+
+        subject.handle(0).unwrap().set(1, 255).unwrap();
+
+        // Wait for the background thread to transmit the value.
+        let start = Instant::now();
+        while subject.devices()[0]
+            .snapshot
+            .read()
+            .unwrap()
+            .get_dmx_value(1)
+            != Ok(255)
+        {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // Dropping the manager stops the device.
+        drop(subject);
+        let start = Instant::now();
+        while !backend.is_closed() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
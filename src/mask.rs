@@ -0,0 +1,93 @@
+use crate::{DmxError, DMX_SLOT_COUNT};
+
+/// A set of channels, e.g. the intensity channels of a rig.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelMask {
+    channels: [bool; DMX_SLOT_COUNT],
+}
+
+impl Default for ChannelMask {
+    fn default() -> Self {
+        ChannelMask::empty()
+    }
+}
+
+impl ChannelMask {
+    /// A mask without any channel.
+    pub fn empty() -> Self {
+        ChannelMask {
+            channels: [false; DMX_SLOT_COUNT],
+        }
+    }
+
+    /// A mask containing every channel. The start code is not part of the mask.
+    pub fn all() -> Self {
+        let mut mask = ChannelMask {
+            channels: [true; DMX_SLOT_COUNT],
+        };
+        mask.channels[0] = false;
+        mask
+    }
+
+    /// Add the given channel to the mask.
+    pub fn insert(&mut self, channel: usize) -> Result<(), DmxError> {
+        if channel == 0 || channel >= DMX_SLOT_COUNT {
+            return Err(DmxError::InvalidChannel(channel));
+        }
+        self.channels[channel] = true;
+
+        Ok(())
+    }
+
+    /// Add len channels beginning with start to the mask.
+    pub fn insert_range(&mut self, start: usize, len: usize) -> Result<(), DmxError> {
+        let end = match start.checked_add(len) {
+            Some(end) if start > 0 && end <= DMX_SLOT_COUNT => end,
+            _ => return Err(DmxError::InvalidChannel(start.saturating_add(len))),
+        };
+        self.channels[start..end].fill(true);
+
+        Ok(())
+    }
+
+    /// Remove the given channel from the mask.
+    pub fn remove(&mut self, channel: usize) -> Result<(), DmxError> {
+        if channel == 0 || channel >= DMX_SLOT_COUNT {
+            return Err(DmxError::InvalidChannel(channel));
+        }
+        self.channels[channel] = false;
+
+        Ok(())
+    }
+
+    /// Whether the given channel is part of the mask. Invalid channels never are.
+    pub fn contains(&self, channel: usize) -> bool {
+        channel < DMX_SLOT_COUNT && self.channels[channel]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_test() {
+        let mut subject = ChannelMask::empty();
+        assert!(!subject.contains(1));
+
+        subject.insert(1).unwrap();
+        subject.insert_range(10, 3).unwrap();
+        assert!(subject.contains(1));
+        assert!(subject.contains(12));
+        assert!(!subject.contains(13));
+
+        subject.remove(1).unwrap();
+        assert!(!subject.contains(1));
+
+        // The start code is never part of a mask.
+        assert_eq!(subject.insert(0), Err(DmxError::InvalidChannel(0)));
+        assert!(!ChannelMask::all().contains(0));
+        assert!(ChannelMask::all().contains(DMX_SLOT_COUNT - 1));
+        assert!(!subject.contains(DMX_SLOT_COUNT));
+    }
+}
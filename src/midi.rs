@@ -0,0 +1,305 @@
+use crate::{DmxError, OpenDMX, OpenDmxHandle, OpenDmxProtocol, SharedSnapshot, DMX_UNIVERSE_SIZE};
+use midir::{MidiInput, MidiInputConnection};
+use std::{collections::HashMap, sync::mpsc::Receiver};
+
+const CONTROL_CHANGE: u8 = 0xB0;
+const NOTE_ON: u8 = 0x90;
+const NOTE_OFF: u8 = 0x80;
+
+/// Assigns MIDI messages to dmx channels (see OpenDMX::bridge_midi()). Messages are accepted on
+/// all MIDI channels.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MidiMapping {
+    controls: HashMap<u8, usize>,
+    blackout_note: Option<u8>,
+    full_on_note: Option<u8>,
+}
+
+impl MidiMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the value of control change cc to the given channel. The MIDI range 0-127 is
+    /// scaled to 0-255.
+    pub fn map_cc(&mut self, cc: u8, channel: usize) -> Result<(), DmxError> {
+        if channel == 0 || channel > DMX_UNIVERSE_SIZE {
+            return Err(DmxError::InvalidChannel(channel));
+        }
+
+        self.controls.insert(cc, channel);
+        Ok(())
+    }
+
+    /// The channel the given control change is mapped to, if any.
+    pub fn channel(&self, cc: u8) -> Option<usize> {
+        self.controls.get(&cc).copied()
+    }
+
+    /// Set all channels to zero while the given note is held.
+    pub fn set_blackout_note(&mut self, note: Option<u8>) {
+        self.blackout_note = note;
+    }
+
+    /// Set all channels to full while the given note is held.
+    pub fn set_full_on_note(&mut self, note: Option<u8>) {
+        self.full_on_note = note;
+    }
+}
+
+/// Scale a MIDI value (0-127) to a dmx value (0-255).
+fn scale(value: u8) -> u8 {
+    ((value.min(127) as u16 * 255 + 63) / 127) as u8
+}
+
+/// Keeps track of the fader levels and the frame before a bump, so both can be restored after
+/// the bump.
+struct BridgeState {
+    mapping: MidiMapping,
+    snapshot: SharedSnapshot,
+    // Channel and level of every fader, that has been moved.
+    levels: HashMap<usize, u8>,
+    bump: Option<u8>,
+    before_bump: [u8; DMX_UNIVERSE_SIZE],
+}
+
+impl BridgeState {
+    fn new(mapping: MidiMapping, snapshot: SharedSnapshot) -> Self {
+        BridgeState {
+            mapping,
+            snapshot,
+            levels: HashMap::new(),
+            bump: None,
+            before_bump: [0; DMX_UNIVERSE_SIZE],
+        }
+    }
+
+    /// Translate a MIDI message into a command for the device. Unmapped messages are ignored.
+    fn handle(&mut self, message: &[u8]) -> Option<OpenDmxProtocol> {
+        let (status, data1, data2) = match message {
+            [status, data1, data2, ..] => (status & 0xF0, *data1, *data2),
+            _ => return None,
+        };
+
+        match status {
+            CONTROL_CHANGE => {
+                let channel = self.mapping.channel(data1)?;
+                let value = scale(data2);
+                self.levels.insert(channel, value);
+
+                // The levels are restored, when the bump is released.
+                if self.bump.is_some() {
+                    return None;
+                }
+                Some(OpenDmxProtocol::SetValue(channel, value))
+            }
+            NOTE_ON if data2 > 0 => {
+                let level = if Some(data1) == self.mapping.blackout_note {
+                    0
+                } else if Some(data1) == self.mapping.full_on_note {
+                    255
+                } else {
+                    return None;
+                };
+
+                // Channels set by other commands keep their values after the bump. The faders
+                // win, their latest levels might not have been transmitted yet.
+                if self.bump.is_none() {
+                    if let Ok(snapshot) = self.snapshot.read() {
+                        self.before_bump.copy_from_slice(&snapshot.values()[1..]);
+                    }
+                }
+                self.bump = Some(data1);
+                Some(OpenDmxProtocol::SendFrame(Box::new(
+                    [level; DMX_UNIVERSE_SIZE],
+                )))
+            }
+            // A note on with velocity 0 is a note off.
+            NOTE_ON | NOTE_OFF => {
+                if self.bump != Some(data1) {
+                    return None;
+                }
+
+                self.bump = None;
+                let mut frame = self.before_bump;
+                for (channel, value) in &self.levels {
+                    frame[channel - 1] = *value;
+                }
+                Some(OpenDmxProtocol::SendFrame(Box::new(frame)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A dmx device, that is controlled from a MIDI input port (see OpenDMX::bridge_midi()). Dropping
+/// the bridge closes the MIDI connection and stops the device.
+pub struct MidiBridge {
+    connection: Option<MidiInputConnection<BridgeState>>,
+    handle: OpenDmxHandle,
+    receiver: Receiver<OpenDmxProtocol>,
+    snapshot: SharedSnapshot,
+}
+
+impl MidiBridge {
+    /// Handle of the device. Can be used to send additional commands.
+    pub fn handle(&self) -> &OpenDmxHandle {
+        &self.handle
+    }
+
+    /// Receiver for the responses of the device.
+    pub fn receiver(&self) -> &Receiver<OpenDmxProtocol> {
+        &self.receiver
+    }
+
+    /// The frame the device is currently transmitting.
+    pub fn snapshot(&self) -> &SharedSnapshot {
+        &self.snapshot
+    }
+}
+
+impl Drop for MidiBridge {
+    fn drop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            connection.close();
+        }
+
+        if self.handle.stop().is_err() {
+            log::debug!("Could not stop dmx device. The device thread is not running");
+        }
+    }
+}
+
+impl OpenDMX {
+    /// Open the device with the given id in a background thread (see run()) and control it from
+    /// the MIDI input port with the given index. Control changes are written to the mapped
+    /// channels, the blackout and full on notes bump all channels while they are held.
+    pub fn bridge_midi(
+        device_id: i32,
+        midi_port: usize,
+        mapping: MidiMapping,
+    ) -> Result<MidiBridge, DmxError> {
+        let input = match MidiInput::new("open_dmx") {
+            Ok(i) => i,
+            Err(_) => return Err(DmxError::Midi("open midi input")),
+        };
+
+        let port = match input.ports().into_iter().nth(midi_port) {
+            Some(p) => p,
+            None => return Err(DmxError::Midi("find midi port")),
+        };
+
+        let (sender, receiver, snapshot) = OpenDMX::run(device_id);
+        let handle = OpenDmxHandle::new(sender);
+        let callback_handle = handle.clone();
+
+        let connection = match input.connect(
+            &port,
+            "open_dmx",
+            move |_, message, state: &mut BridgeState| {
+                if let Some(cmd) = state.handle(message) {
+                    if callback_handle.send(cmd).is_err() {
+                        log::debug!(
+                            "Could not send midi command. The device thread is not running"
+                        );
+                    }
+                }
+            },
+            BridgeState::new(mapping, snapshot.clone()),
+        ) {
+            Ok(c) => c,
+            Err(_) => {
+                let _ = handle.stop();
+                return Err(DmxError::Midi("connect to midi port"));
+            }
+        };
+
+        Ok(MidiBridge {
+            connection: Some(connection),
+            handle,
+            receiver,
+            snapshot,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DMX_SLOT_COUNT;
+
+    use super::*;
+
+    fn frame(cmd: Option<OpenDmxProtocol>) -> [u8; DMX_UNIVERSE_SIZE] {
+        match cmd {
+            Some(OpenDmxProtocol::SendFrame(frame)) => *frame,
+            other => panic!("Expected a frame, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn midi_mapping_test() {
+        let mut mapping = MidiMapping::new();
+        mapping.map_cc(7, 1).unwrap();
+        mapping.set_full_on_note(Some(60));
+        mapping.set_blackout_note(Some(61));
+        assert_eq!(mapping.map_cc(8, 0), Err(DmxError::InvalidChannel(0)));
+        assert_eq!(mapping.map_cc(8, 513), Err(DmxError::InvalidChannel(513)));
+
+        assert_eq!(scale(0), 0);
+        assert_eq!(scale(64), 129);
+        assert_eq!(scale(127), 255);
+
+        let mut state = BridgeState::new(mapping, SharedSnapshot::default());
+        assert!(matches!(
+            state.handle(&[0xB3, 7, 127]),
+            Some(OpenDmxProtocol::SetValue(1, 255))
+        ));
+        // Unmapped controls and unknown messages are ignored.
+        assert!(state.handle(&[0xB0, 8, 127]).is_none());
+        assert!(state.handle(&[0xE0, 0, 64]).is_none());
+        assert!(state.handle(&[0xB0]).is_none());
+
+        assert_eq!(
+            frame(state.handle(&[0x90, 61, 100])),
+            [0; DMX_UNIVERSE_SIZE]
+        );
+        // Fader moves during a bump are applied on release.
+        assert!(state.handle(&[0xB0, 7, 64]).is_none());
+        let released = frame(state.handle(&[0x90, 61, 0]));
+        assert_eq!(released[0], 129);
+        assert_eq!(released[1], 0);
+
+        assert_eq!(
+            frame(state.handle(&[0x90, 60, 100])),
+            [255; DMX_UNIVERSE_SIZE]
+        );
+        assert!(state.handle(&[0x80, 61, 0]).is_none());
+        assert_eq!(frame(state.handle(&[0x80, 60, 0]))[0], 129);
+    }
+
+    #[test]
+    fn midi_bump_restore_test() {
+        let mut mapping = MidiMapping::new();
+        mapping.map_cc(7, 1).unwrap();
+        mapping.set_blackout_note(Some(61));
+
+        // Channel 5 has been set through the handle of the bridge.
+        let snapshot = SharedSnapshot::default();
+        let mut transmitted = [0; DMX_SLOT_COUNT];
+        transmitted[5] = 77;
+        snapshot.write().unwrap().update(&transmitted);
+
+        let mut state = BridgeState::new(mapping, snapshot);
+        assert!(state.handle(&[0xB0, 7, 127]).is_some());
+        assert_eq!(
+            frame(state.handle(&[0x90, 61, 100])),
+            [0; DMX_UNIVERSE_SIZE]
+        );
+
+        let released = frame(state.handle(&[0x80, 61, 0]));
+        assert_eq!(released[4], 77);
+        // The fader level has not been transmitted before the bump, but is restored as well.
+        assert_eq!(released[0], 255);
+        assert_eq!(released[1], 0);
+    }
+}
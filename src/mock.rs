@@ -0,0 +1,403 @@
+use crate::{DmxBackend, FlowControl, RtsState};
+use libftd2xx::{
+    BitsPerWord, DeviceInfo, DeviceStatus, DeviceType, FtStatus, Parity, StopBits, TimeoutError,
+    Version,
+};
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// What happened on the line of a MockBackend (see MockBackend::line_events()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockLineEvent {
+    BreakOn,
+    BreakOff,
+    /// Data has been written. Contains the number of bytes.
+    Write(usize),
+    /// The RTS line has been set or cleared.
+    Rts(RtsState),
+}
+
+/// A backend that keeps everything in memory instead of talking to a device. All clones share
+/// the same state, so a test can keep a clone to inspect what the device has been sent.
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    info: DeviceInfo,
+    writes: Vec<Vec<u8>>,
+    line_events: Vec<(Instant, MockLineEvent)>,
+    break_on: bool,
+    latency_timer: Duration,
+    usb_transfer_size: Option<u32>,
+    flow_control: FlowControl,
+    rts: RtsState,
+    rx: Vec<u8>,
+    write_limit: Option<usize>,
+    event_status: u32,
+    tx_queue: u32,
+    ignore_latency_timer: bool,
+    transient_failures: usize,
+    unplugged: bool,
+    #[cfg(feature = "eeprom")]
+    eeprom: Vec<u8>,
+    #[cfg(feature = "eeprom")]
+    ignore_eeprom_writes: bool,
+    resets: usize,
+    closed: bool,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        MockState {
+            info: DeviceInfo {
+                description: "Mock".to_owned(),
+                ..Default::default()
+            },
+            writes: Vec::new(),
+            line_events: Vec::new(),
+            break_on: false,
+            // The ftdi default.
+            latency_timer: Duration::from_millis(16),
+            usb_transfer_size: None,
+            flow_control: FlowControl::None,
+            rts: RtsState::Clear,
+            rx: Vec::new(),
+            write_limit: None,
+            event_status: 0,
+            tx_queue: 0,
+            ignore_latency_timer: false,
+            transient_failures: 0,
+            unplugged: false,
+            // A small, empty user area.
+            #[cfg(feature = "eeprom")]
+            eeprom: vec![0; 64],
+            #[cfg(feature = "eeprom")]
+            ignore_eeprom_writes: false,
+            resets: 0,
+            closed: false,
+        }
+    }
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a backend, that reports the given chip.
+    pub fn with_device_type(device_type: DeviceType) -> Self {
+        let backend = Self::default();
+        backend.state().info.device_type = device_type;
+        backend
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    /// All data that has been written to the device, one entry per write.
+    pub fn writes(&self) -> Vec<Vec<u8>> {
+        self.state().writes.clone()
+    }
+
+    /// The data of the latest write, if any.
+    pub fn last_write(&self) -> Option<Vec<u8>> {
+        self.state().writes.last().cloned()
+    }
+
+    /// Breaks and writes in the order they happened, with the time they happened.
+    pub fn line_events(&self) -> Vec<(Instant, MockLineEvent)> {
+        self.state().line_events.clone()
+    }
+
+    /// Whether the break condition is currently set.
+    pub fn is_break_on(&self) -> bool {
+        self.state().break_on
+    }
+
+    /// The USB transfer size set by the latest reset, if any.
+    pub fn usb_transfer_size(&self) -> Option<u32> {
+        self.state().usb_transfer_size
+    }
+
+    /// The flow control set by the latest reset.
+    pub fn flow_control(&self) -> FlowControl {
+        self.state().flow_control
+    }
+
+    /// The RTS state set by the latest reset.
+    pub fn rts(&self) -> RtsState {
+        self.state().rts
+    }
+
+    /// Number of times the device has been reset.
+    pub fn resets(&self) -> usize {
+        self.state().resets
+    }
+
+    /// Whether the device has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.state().closed
+    }
+
+    /// Accept at most the given number of bytes per write() to simulate a troubled link.
+    pub fn set_write_limit(&self, limit: Option<usize>) {
+        self.state().write_limit = limit;
+    }
+
+    /// Set the event status reported by the device.
+    pub fn set_event_status(&self, event_status: u32) {
+        self.state().event_status = event_status;
+    }
+
+    /// Set the number of bytes in the transmit queue. Every status() call sends one of them.
+    pub fn set_tx_queue(&self, bytes: u32) {
+        self.state().tx_queue = bytes;
+    }
+
+    /// Number of bytes still in the transmit queue.
+    pub fn tx_queue(&self) -> u32 {
+        self.state().tx_queue
+    }
+
+    /// Silently ignore changes of the latency timer, like a device on a flaky USB hub.
+    pub fn set_ignore_latency_timer(&self, ignore: bool) {
+        self.state().ignore_latency_timer = ignore;
+    }
+
+    /// Silently ignore writes to the EEPROM user area, like a write protected EEPROM.
+    #[cfg(feature = "eeprom")]
+    pub fn set_ignore_eeprom_writes(&self, ignore: bool) {
+        self.state().ignore_eeprom_writes = ignore;
+    }
+
+    /// Fail the next calls of set_baud_rate() and purge_rx() with FtStatus::IO_ERROR, like a
+    /// device on a flaky USB hub.
+    pub fn set_transient_failures(&self, failures: usize) {
+        self.state().transient_failures = failures;
+    }
+
+    /// Fail set_baud_rate(), purge_rx() and set_break() with FtStatus::DEVICE_NOT_FOUND, like a
+    /// device that has been unplugged.
+    pub fn set_unplugged(&self, unplugged: bool) {
+        self.state().unplugged = unplugged;
+    }
+
+    /// Fail like an unplugged device or with FtStatus::IO_ERROR, if a transient failure is
+    /// pending.
+    fn transient_failure(&self) -> Result<(), FtStatus> {
+        let mut state = self.state();
+        if state.unplugged {
+            return Err(FtStatus::DEVICE_NOT_FOUND);
+        }
+        if state.transient_failures > 0 {
+            state.transient_failures -= 1;
+            return Err(FtStatus::IO_ERROR);
+        }
+        Ok(())
+    }
+
+    /// Queue data, that will be returned by the next read.
+    pub fn push_rx(&self, data: &[u8]) {
+        self.state().rx.extend_from_slice(data);
+    }
+}
+
+impl DmxBackend for MockBackend {
+    fn device_info(&mut self) -> Result<DeviceInfo, FtStatus> {
+        Ok(self.state().info.clone())
+    }
+
+    fn driver_version(&mut self) -> Result<Version, FtStatus> {
+        Ok(Version::new(1, 0, 0))
+    }
+
+    fn reset(&mut self) -> Result<(), FtStatus> {
+        self.state().resets += 1;
+        Ok(())
+    }
+
+    fn set_baud_rate(&mut self, _baud_rate: u32) -> Result<(), FtStatus> {
+        self.transient_failure()
+    }
+
+    fn set_data_characteristics(
+        &mut self,
+        _bits_per_word: BitsPerWord,
+        _stop_bits: StopBits,
+        _parity: Parity,
+    ) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_timeouts(
+        &mut self,
+        _read_time_out: Duration,
+        _write_time_out: Duration,
+    ) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_latency_timer(&mut self, timer: Duration) -> Result<(), FtStatus> {
+        let mut state = self.state();
+        if !state.ignore_latency_timer {
+            state.latency_timer = timer;
+        }
+        Ok(())
+    }
+
+    fn latency_timer(&mut self) -> Result<Duration, FtStatus> {
+        Ok(self.state().latency_timer)
+    }
+
+    fn set_usb_parameters(&mut self, transfer_size: u32) -> Result<(), FtStatus> {
+        self.state().usb_transfer_size = Some(transfer_size);
+        Ok(())
+    }
+
+    fn set_flow_control_none(&mut self) -> Result<(), FtStatus> {
+        self.state().flow_control = FlowControl::None;
+        Ok(())
+    }
+
+    fn set_flow_control_rts_cts(&mut self) -> Result<(), FtStatus> {
+        self.state().flow_control = FlowControl::RtsCts;
+        Ok(())
+    }
+
+    fn set_flow_control_dtr_dsr(&mut self) -> Result<(), FtStatus> {
+        self.state().flow_control = FlowControl::DtrDsr;
+        Ok(())
+    }
+
+    fn set_flow_control_xon_xoff(&mut self, xon: u8, xoff: u8) -> Result<(), FtStatus> {
+        self.state().flow_control = FlowControl::XonXoff { xon, xoff };
+        Ok(())
+    }
+
+    fn set_rts(&mut self) -> Result<(), FtStatus> {
+        let mut state = self.state();
+        state.rts = RtsState::Set;
+        state
+            .line_events
+            .push((Instant::now(), MockLineEvent::Rts(RtsState::Set)));
+        Ok(())
+    }
+
+    fn clear_rts(&mut self) -> Result<(), FtStatus> {
+        let mut state = self.state();
+        state.rts = RtsState::Clear;
+        state
+            .line_events
+            .push((Instant::now(), MockLineEvent::Rts(RtsState::Clear)));
+        Ok(())
+    }
+
+    fn purge_rx(&mut self) -> Result<(), FtStatus> {
+        self.transient_failure()?;
+        self.state().rx.clear();
+        Ok(())
+    }
+
+    fn purge_tx(&mut self) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_break(&mut self, on: bool) -> Result<(), FtStatus> {
+        let mut state = self.state();
+        if state.unplugged {
+            return Err(FtStatus::DEVICE_NOT_FOUND);
+        }
+        state.break_on = on;
+        let event = if on {
+            MockLineEvent::BreakOn
+        } else {
+            MockLineEvent::BreakOff
+        };
+        state.line_events.push((Instant::now(), event));
+        Ok(())
+    }
+
+    fn queue_status(&mut self) -> Result<usize, FtStatus> {
+        Ok(self.state().rx.len())
+    }
+
+    fn read_all(&mut self, buf: &mut [u8]) -> Result<(), TimeoutError> {
+        let mut state = self.state();
+        if state.rx.len() < buf.len() {
+            return Err(TimeoutError::Timeout {
+                actual: state.rx.len(),
+                expected: buf.len(),
+            });
+        }
+
+        let data: Vec<u8> = state.rx.drain(..buf.len()).collect();
+        buf.copy_from_slice(&data);
+        Ok(())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), TimeoutError> {
+        let mut state = self.state();
+        state.writes.push(buf.to_vec());
+        state
+            .line_events
+            .push((Instant::now(), MockLineEvent::Write(buf.len())));
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, FtStatus> {
+        let mut state = self.state();
+        let len = state.write_limit.map_or(buf.len(), |l| l.min(buf.len()));
+        state.writes.push(buf[..len].to_vec());
+        state
+            .line_events
+            .push((Instant::now(), MockLineEvent::Write(len)));
+        Ok(len)
+    }
+
+    fn status(&mut self) -> Result<DeviceStatus, FtStatus> {
+        let mut state = self.state();
+        let status = DeviceStatus {
+            ammount_in_rx_queue: state.rx.len() as u32,
+            ammount_in_tx_queue: state.tx_queue,
+            event_status: state.event_status,
+        };
+        state.tx_queue = state.tx_queue.saturating_sub(1);
+        Ok(status)
+    }
+
+    fn close(&mut self) -> Result<(), FtStatus> {
+        self.state().closed = true;
+        Ok(())
+    }
+
+    #[cfg(feature = "eeprom")]
+    fn eeprom_user_size(&mut self) -> Result<usize, FtStatus> {
+        Ok(self.state().eeprom.len())
+    }
+
+    #[cfg(feature = "eeprom")]
+    fn eeprom_user_read(&mut self, buf: &mut [u8]) -> Result<usize, FtStatus> {
+        let state = self.state();
+        let len = buf.len().min(state.eeprom.len());
+        buf[..len].copy_from_slice(&state.eeprom[..len]);
+        Ok(len)
+    }
+
+    #[cfg(feature = "eeprom")]
+    fn eeprom_user_write(&mut self, buf: &[u8]) -> Result<(), FtStatus> {
+        let mut state = self.state();
+        if buf.len() > state.eeprom.len() {
+            return Err(FtStatus::EEPROM_WRITE_FAILED);
+        }
+        if !state.ignore_eeprom_writes {
+            state.eeprom[..buf.len()].copy_from_slice(buf);
+        }
+        Ok(())
+    }
+}
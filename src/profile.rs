@@ -0,0 +1,70 @@
+use crate::{DEFAULT_LATENCY_TIMER, DEFAULT_USB_TRANSFER_SIZE};
+use libftd2xx::DeviceType;
+use std::time::Duration;
+
+/// Settings that work well for a family of ftdi chips. The profile is picked from the device
+/// type when the device is opened. Settings configured on the OpenDmxBuilder take precedence.
+///
+/// All supported chips derive 250000 baud exactly from their clock, so the baud rate is the
+/// same for every profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceProfile {
+    pub latency_timer: Duration,
+    /// None keeps the driver defaults.
+    pub usb_transfer_size: Option<u32>,
+    pub baud_rate: u32,
+}
+
+impl Default for DeviceProfile {
+    fn default() -> Self {
+        DeviceProfile {
+            latency_timer: DEFAULT_LATENCY_TIMER,
+            usb_transfer_size: Some(DEFAULT_USB_TRANSFER_SIZE),
+            baud_rate: 250000,
+        }
+    }
+}
+
+impl DeviceProfile {
+    /// The profile for the given chip. Unknown chips use the default profile.
+    pub fn for_device_type(device_type: DeviceType) -> Self {
+        match device_type {
+            // The chip of the Enttec Open DMX USB and most clones.
+            DeviceType::FT232R => DeviceProfile::default(),
+            // High speed chips use 512 byte USB packets, so a full frame fits into two packets.
+            DeviceType::FT232H | DeviceType::FT2232H | DeviceType::FT4232H => DeviceProfile {
+                latency_timer: Duration::from_millis(1),
+                usb_transfer_size: Some(1024),
+                ..Default::default()
+            },
+            // The X series flushes reliably with the shortest latency timer.
+            DeviceType::FT_X_SERIES => DeviceProfile {
+                latency_timer: Duration::from_millis(1),
+                ..Default::default()
+            },
+            _ => DeviceProfile::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_test() {
+        assert_eq!(
+            DeviceProfile::for_device_type(DeviceType::Unknown),
+            DeviceProfile::default()
+        );
+        assert_eq!(
+            DeviceProfile::for_device_type(DeviceType::FT232R),
+            DeviceProfile::default()
+        );
+
+        let profile = DeviceProfile::for_device_type(DeviceType::FT232H);
+        assert_eq!(profile.latency_timer, Duration::from_millis(1));
+        assert_eq!(profile.usb_transfer_size, Some(1024));
+        assert_eq!(profile.baud_rate, 250000);
+    }
+}
@@ -0,0 +1,523 @@
+use crate::{DmxError, OpenDMX, RtsState, RDM_START_CODE};
+use std::{
+    fmt,
+    sync::atomic::{AtomicU8, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Parameter id of the device info of a responder.
+pub const PID_DEVICE_INFO: u16 = 0x0060;
+/// Parameter id of the dmx start address of a responder.
+pub const PID_DMX_START_ADDRESS: u16 = 0x00F0;
+
+const PID_DISC_UNIQUE_BRANCH: u16 = 0x0001;
+const PID_DISC_MUTE: u16 = 0x0002;
+const PID_DISC_UN_MUTE: u16 = 0x0003;
+
+const SUB_START_CODE: u8 = 0x01;
+const DISCOVERY_COMMAND: u8 = 0x10;
+const GET_COMMAND: u8 = 0x20;
+const SET_COMMAND: u8 = 0x30;
+
+const RESPONSE_TYPE_ACK: u8 = 0x00;
+const RESPONSE_TYPE_NACK_REASON: u8 = 0x02;
+
+const DISCOVERY_PREAMBLE: u8 = 0xFE;
+const DISCOVERY_SEPARATOR: u8 = 0xAA;
+
+/// Size of a packet without parameter data and checksum.
+const HEADER_SIZE: usize = 24;
+
+/// Time to wait for a response. Responders answer within 2.8ms, the rest covers the USB
+/// latency.
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(20);
+
+/// Shortest break in front of a request allowed by E1.20. Responders ignore requests with the
+/// shorter dmx break.
+const RDM_MIN_BREAK: Duration = Duration::from_micros(176);
+/// Range of the mark after break in front of a request allowed by E1.20.
+const RDM_MIN_MAB: Duration = Duration::from_micros(12);
+const RDM_MAX_MAB: Duration = Duration::from_micros(88);
+
+/// How the line driver of the adapter is switched between transmitting a request and receiving
+/// the response (see OpenDmxBuilder::rdm_turnaround()).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RdmTurnaround {
+    /// The adapter switches the direction by itself. Transmit only adapters, like the Enttec
+    /// Open DMX USB, never receive a response.
+    #[default]
+    Automatic,
+    /// The driver enable of the line driver is wired to RTS: RTS is set while the request is
+    /// transmitted and cleared while waiting for the response. Afterwards the configured state
+    /// is restored (see OpenDmxBuilder::rts()).
+    Rts,
+}
+
+/// Uid of this controller. Taken from the range reserved for development.
+const CONTROLLER_UID: Uid = Uid {
+    manufacturer: 0x7FF0,
+    device: 1,
+};
+
+static TRANSACTION_NUMBER: AtomicU8 = AtomicU8::new(0);
+
+/// Unique id of an RDM device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uid {
+    pub manufacturer: u16,
+    pub device: u32,
+}
+
+impl Uid {
+    /// Addresses all devices.
+    pub const BROADCAST: Uid = Uid {
+        manufacturer: 0xFFFF,
+        device: 0xFFFF_FFFF,
+    };
+
+    /// Highest uid, that can be assigned to a device.
+    const MAX: u64 = 0xFFFF_FFFF_FFFE;
+
+    fn from_u64(value: u64) -> Self {
+        Uid {
+            manufacturer: (value >> 32) as u16,
+            device: value as u32,
+        }
+    }
+
+    fn to_u64(self) -> u64 {
+        ((self.manufacturer as u64) << 32) | self.device as u64
+    }
+
+    fn to_bytes(self) -> [u8; 6] {
+        let mut bytes = [0; 6];
+        bytes.copy_from_slice(&self.to_u64().to_be_bytes()[2..]);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut value = [0; 8];
+        value[2..].copy_from_slice(&bytes[..6]);
+        Uid::from_u64(u64::from_be_bytes(value))
+    }
+}
+
+impl fmt::Display for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04X}:{:08X}", self.manufacturer, self.device)
+    }
+}
+
+fn checksum(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |sum, b| sum.wrapping_add(*b as u16))
+}
+
+/// Build a request packet including the checksum.
+fn packet(destination: Uid, transaction: u8, command_class: u8, pid: u16, data: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(HEADER_SIZE + data.len() + 2);
+    packet.push(RDM_START_CODE);
+    packet.push(SUB_START_CODE);
+    packet.push((HEADER_SIZE + data.len()) as u8);
+    packet.extend_from_slice(&destination.to_bytes());
+    packet.extend_from_slice(&CONTROLLER_UID.to_bytes());
+    packet.push(transaction);
+    // Port id
+    packet.push(1);
+    // Message count
+    packet.push(0);
+    // Sub device
+    packet.extend_from_slice(&[0, 0]);
+    packet.push(command_class);
+    packet.extend_from_slice(&pid.to_be_bytes());
+    packet.push(data.len() as u8);
+    packet.extend_from_slice(data);
+
+    let sum = checksum(&packet);
+    packet.extend_from_slice(&sum.to_be_bytes());
+    packet
+}
+
+/// Find the response of the given device to the given command in the received data and return
+/// its parameter data. The received data might contain the echo of the request as well.
+fn parse_response(
+    data: &[u8],
+    source: Uid,
+    command_class: u8,
+    pid: u16,
+) -> Result<Vec<u8>, DmxError> {
+    for start in 0..data.len() {
+        let packet = &data[start..];
+        if packet.len() < HEADER_SIZE + 2
+            || packet[0] != RDM_START_CODE
+            || packet[1] != SUB_START_CODE
+        {
+            continue;
+        }
+
+        let len = packet[2] as usize;
+        if len < HEADER_SIZE || packet.len() < len + 2 {
+            continue;
+        }
+
+        let sum = u16::from_be_bytes([packet[len], packet[len + 1]]);
+        if checksum(&packet[..len]) != sum
+            || Uid::from_bytes(&packet[9..15]) != source
+            || packet[20] != command_class + 1
+            || u16::from_be_bytes([packet[21], packet[22]]) != pid
+        {
+            continue;
+        }
+
+        let pdl = (packet[23] as usize).min(len - HEADER_SIZE);
+        let parameter_data = &packet[HEADER_SIZE..HEADER_SIZE + pdl];
+        return match packet[16] {
+            RESPONSE_TYPE_ACK => Ok(parameter_data.to_vec()),
+            RESPONSE_TYPE_NACK_REASON if pdl >= 2 => Err(DmxError::RdmNack(u16::from_be_bytes([
+                parameter_data[0],
+                parameter_data[1],
+            ]))),
+            _ => Err(DmxError::Rdm("handle rdm response type")),
+        };
+    }
+
+    Err(DmxError::Rdm("receive a rdm response"))
+}
+
+/// Result of a discovery request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Discovery {
+    /// No device answered.
+    None,
+    /// Exactly one device answered.
+    Found(Uid),
+    /// Several devices answered at once and the responses collided.
+    Collision,
+}
+
+/// Decode the response to a discovery request. The uid and the checksum are encoded as two
+/// bytes each, one or'ed with 0xAA and one or'ed with 0x55.
+fn parse_discovery(data: &[u8]) -> Discovery {
+    if data.is_empty() {
+        return Discovery::None;
+    }
+
+    let start = match data.iter().position(|b| *b == DISCOVERY_SEPARATOR) {
+        Some(p) if data[..p].iter().all(|b| *b == DISCOVERY_PREAMBLE) => p + 1,
+        _ => return Discovery::Collision,
+    };
+
+    let encoded = &data[start..];
+    if encoded.len() < 16 {
+        return Discovery::Collision;
+    }
+
+    let sum = checksum(&encoded[..12]);
+    let decoded: Vec<u8> = encoded[..16].chunks(2).map(|b| b[0] & b[1]).collect();
+    if u16::from_be_bytes([decoded[6], decoded[7]]) != sum {
+        return Discovery::Collision;
+    }
+
+    Discovery::Found(Uid::from_bytes(&decoded[..6]))
+}
+
+fn branch_data(lower: u64, upper: u64) -> Vec<u8> {
+    let mut data = Uid::from_u64(lower).to_bytes().to_vec();
+    data.extend_from_slice(&Uid::from_u64(upper).to_bytes());
+    data
+}
+
+impl OpenDMX {
+    /// Find all RDM responders on the line using the unique branch algorithm.
+    ///
+    /// RDM needs the adapter to switch its line driver to receive after each request, either by
+    /// itself or through RTS (see RdmTurnaround). The Enttec Open DMX USB and most clones are
+    /// transmit only, so RDM does not work with them.
+    pub fn rdm_discover(&mut self) -> Result<Vec<Uid>, DmxError> {
+        self.rdm_send(Uid::BROADCAST, DISCOVERY_COMMAND, PID_DISC_UN_MUTE, &[])?;
+
+        let mut found = Vec::new();
+        self.rdm_discover_branch(0, Uid::MAX, &mut found)?;
+        Ok(found)
+    }
+
+    /// Read the given parameter of the given device.
+    pub fn rdm_get(&mut self, uid: Uid, pid: u16) -> Result<Vec<u8>, DmxError> {
+        let data = self.rdm_send(uid, GET_COMMAND, pid, &[])?;
+        parse_response(&data, uid, GET_COMMAND, pid)
+    }
+
+    /// Change the given parameter of the given device.
+    pub fn rdm_set(&mut self, uid: Uid, pid: u16, value: &[u8]) -> Result<(), DmxError> {
+        let data = self.rdm_send(uid, SET_COMMAND, pid, value)?;
+        parse_response(&data, uid, SET_COMMAND, pid).map(|_| ())
+    }
+
+    /// Read the dmx start address of the given device.
+    pub fn rdm_start_address(&mut self, uid: Uid) -> Result<u16, DmxError> {
+        let data = self.rdm_get(uid, PID_DMX_START_ADDRESS)?;
+        if data.len() < 2 {
+            return Err(DmxError::Rdm("read the dmx start address"));
+        }
+        Ok(u16::from_be_bytes([data[0], data[1]]))
+    }
+
+    /// Change the dmx start address of the given device.
+    pub fn rdm_set_start_address(&mut self, uid: Uid, address: u16) -> Result<(), DmxError> {
+        self.rdm_set(uid, PID_DMX_START_ADDRESS, &address.to_be_bytes())
+    }
+
+    fn rdm_discover_branch(
+        &mut self,
+        lower: u64,
+        upper: u64,
+        found: &mut Vec<Uid>,
+    ) -> Result<(), DmxError> {
+        loop {
+            let data = branch_data(lower, upper);
+            let response = self.rdm_send(
+                Uid::BROADCAST,
+                DISCOVERY_COMMAND,
+                PID_DISC_UNIQUE_BRANCH,
+                &data,
+            )?;
+
+            match parse_discovery(&response) {
+                Discovery::None => return Ok(()),
+                Discovery::Found(uid) => {
+                    // A device, that does not mute, would answer forever.
+                    if found.contains(&uid) {
+                        return Ok(());
+                    }
+
+                    self.rdm_send(uid, DISCOVERY_COMMAND, PID_DISC_MUTE, &[])?;
+                    found.push(uid);
+                }
+                Discovery::Collision => {
+                    if lower == upper {
+                        return Ok(());
+                    }
+
+                    let middle = lower + (upper - lower) / 2;
+                    self.rdm_discover_branch(lower, middle, found)?;
+                    return self.rdm_discover_branch(middle + 1, upper, found);
+                }
+            }
+        }
+    }
+
+    /// Transmit a request and return the data received until the response time out
+    /// elapses. Broadcasts do not wait for a response, except discovery requests. The line is
+    /// turned around as configured and restored afterwards, even if the request fails.
+    fn rdm_send(
+        &mut self,
+        destination: Uid,
+        command_class: u8,
+        pid: u16,
+        data: &[u8],
+    ) -> Result<Vec<u8>, DmxError> {
+        self.ensure_ready()?;
+
+        let transaction = TRANSACTION_NUMBER.fetch_add(1, Ordering::Relaxed);
+        let request = packet(destination, transaction, command_class, pid, data);
+        let wait = destination != Uid::BROADCAST || pid == PID_DISC_UNIQUE_BRANCH;
+
+        let result = self.rdm_transaction(&request, wait);
+        if self.rdm_turnaround == RdmTurnaround::Rts {
+            self.apply_rts(self.rts)?;
+        }
+        result
+    }
+
+    fn rdm_transaction(&mut self, request: &[u8], wait: bool) -> Result<Vec<u8>, DmxError> {
+        let turnaround = self.rdm_turnaround == RdmTurnaround::Rts;
+        if turnaround {
+            self.apply_rts(RtsState::Set)?;
+        }
+
+        self.purge()?;
+        self.set_break(true)?;
+        thread::sleep(self.break_time.max(RDM_MIN_BREAK));
+        self.set_break(false)?;
+        thread::sleep(self.mark_after_break.clamp(RDM_MIN_MAB, RDM_MAX_MAB));
+
+        match self.backend.write_all(request) {
+            Ok(_) => {}
+            Err(e) => return Err(DmxError::from_timeout("write rdm request", e)),
+        }
+
+        if !wait {
+            return Ok(Vec::new());
+        }
+
+        // The responder starts answering within 176us after the request, so the driver has to
+        // be released as soon as the request has left the adapter.
+        if turnaround {
+            if !self.flush(RESPONSE_TIMEOUT)? {
+                return Err(DmxError::Rdm("transmit rdm request"));
+            }
+            self.apply_rts(RtsState::Clear)?;
+        }
+
+        let start = Instant::now();
+        let mut response = Vec::new();
+        while start.elapsed() < RESPONSE_TIMEOUT {
+            let received = self.receive()?;
+            if received.is_empty() && !response.is_empty() {
+                break;
+            }
+
+            response.extend_from_slice(&received);
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MockBackend, MockLineEvent, OpenDmxBuilder};
+
+    use super::*;
+
+    const UID: Uid = Uid {
+        manufacturer: 0x1234,
+        device: 0x5678_9ABC,
+    };
+
+    fn response(response_type: u8, command_class: u8, pid: u16, data: &[u8]) -> Vec<u8> {
+        let mut response = packet(CONTROLLER_UID, 0, command_class + 1, pid, data);
+        // Swap destination and source.
+        response[3..9].copy_from_slice(&CONTROLLER_UID.to_bytes());
+        response[9..15].copy_from_slice(&UID.to_bytes());
+        response[16] = response_type;
+
+        let len = response.len() - 2;
+        let sum = checksum(&response[..len]);
+        response[len..].copy_from_slice(&sum.to_be_bytes());
+        response
+    }
+
+    #[test]
+    fn mock_rdm_turnaround_test() {
+        for turnaround in [RdmTurnaround::Automatic, RdmTurnaround::Rts] {
+            let backend = MockBackend::new();
+            let mut subject = OpenDmxBuilder::new(0)
+                .rdm_turnaround(turnaround)
+                .build_with_backend(Box::new(backend.clone()))
+                .unwrap();
+            subject.reset().unwrap();
+            let skip = backend.line_events().len();
+
+            // No responder answers.
+            assert!(subject.rdm_start_address(UID).is_err());
+
+            let events = backend.line_events().split_off(skip);
+            let kinds: Vec<MockLineEvent> = events.iter().map(|(_, kind)| *kind).collect();
+            let request = MockLineEvent::Write(HEADER_SIZE + 2);
+            let expected = match turnaround {
+                RdmTurnaround::Automatic => {
+                    vec![MockLineEvent::BreakOn, MockLineEvent::BreakOff, request]
+                }
+                // Transmit, receive, then back to the configured state.
+                RdmTurnaround::Rts => vec![
+                    MockLineEvent::Rts(RtsState::Set),
+                    MockLineEvent::BreakOn,
+                    MockLineEvent::BreakOff,
+                    request,
+                    MockLineEvent::Rts(RtsState::Clear),
+                    MockLineEvent::Rts(RtsState::Clear),
+                ],
+            };
+            assert_eq!(kinds, expected);
+
+            // The default dmx timing is too short for RDM.
+            let on = kinds
+                .iter()
+                .position(|k| *k == MockLineEvent::BreakOn)
+                .unwrap();
+            assert!(events[on + 1].0 - events[on].0 >= RDM_MIN_BREAK);
+            assert!(events[on + 2].0 - events[on + 1].0 >= RDM_MIN_MAB);
+        }
+    }
+
+    #[test]
+    fn uid_test() {
+        assert_eq!(UID.to_bytes(), [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC]);
+        assert_eq!(Uid::from_bytes(&UID.to_bytes()), UID);
+        assert_eq!(Uid::from_u64(UID.to_u64()), UID);
+        assert_eq!(UID.to_string(), "1234:56789ABC");
+    }
+
+    #[test]
+    fn packet_test() {
+        let subject = packet(UID, 7, GET_COMMAND, PID_DMX_START_ADDRESS, &[]);
+        assert_eq!(subject.len(), HEADER_SIZE + 2);
+        assert_eq!(subject[0], RDM_START_CODE);
+        assert_eq!(subject[2] as usize, HEADER_SIZE);
+        assert_eq!(Uid::from_bytes(&subject[3..9]), UID);
+        assert_eq!(subject[15], 7);
+        assert_eq!(subject[20], GET_COMMAND);
+        assert_eq!(&subject[21..23], &[0x00, 0xF0]);
+
+        let sum = checksum(&subject[..HEADER_SIZE]);
+        assert_eq!(&subject[HEADER_SIZE..], &sum.to_be_bytes());
+    }
+
+    #[test]
+    fn parse_response_test() {
+        // The echo of the request is skipped.
+        let mut data = packet(UID, 0, GET_COMMAND, PID_DMX_START_ADDRESS, &[]);
+        data.extend(response(
+            RESPONSE_TYPE_ACK,
+            GET_COMMAND,
+            PID_DMX_START_ADDRESS,
+            &[0, 10],
+        ));
+        assert_eq!(
+            parse_response(&data, UID, GET_COMMAND, PID_DMX_START_ADDRESS),
+            Ok(vec![0, 10])
+        );
+
+        let data = response(
+            RESPONSE_TYPE_NACK_REASON,
+            SET_COMMAND,
+            PID_DMX_START_ADDRESS,
+            &[0, 6],
+        );
+        assert_eq!(
+            parse_response(&data, UID, SET_COMMAND, PID_DMX_START_ADDRESS),
+            Err(DmxError::RdmNack(6))
+        );
+
+        let mut data = response(RESPONSE_TYPE_ACK, GET_COMMAND, PID_DEVICE_INFO, &[1]);
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        assert_eq!(
+            parse_response(&data, UID, GET_COMMAND, PID_DEVICE_INFO),
+            Err(DmxError::Rdm("receive a rdm response"))
+        );
+    }
+
+    #[test]
+    fn parse_discovery_test() {
+        let uid = UID.to_bytes();
+        let sum = uid
+            .iter()
+            .fold(0u16, |s, b| s + (*b | 0xAA) as u16 + (*b | 0x55) as u16);
+
+        let mut data = vec![DISCOVERY_PREAMBLE; 7];
+        data.push(DISCOVERY_SEPARATOR);
+        for b in uid.iter().chain(sum.to_be_bytes().iter()) {
+            data.push(b | 0xAA);
+            data.push(b | 0x55);
+        }
+
+        assert_eq!(parse_discovery(&data), Discovery::Found(UID));
+        assert_eq!(parse_discovery(&[]), Discovery::None);
+
+        data[10] = 0;
+        assert_eq!(parse_discovery(&data), Discovery::Collision);
+    }
+}
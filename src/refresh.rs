@@ -0,0 +1,93 @@
+use crate::{DmxError, UpdateFrequency};
+use std::time::Duration;
+
+/// How often the background thread started by OpenDMX::run() transmits frames.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshMode {
+    /// Transmit at the update frequency all the time (see OpenDmxBuilder::update_frequency()).
+    #[default]
+    Steady,
+    /// Transmit active_fps frames per second while the buffer changes, e.g. during a fade, and
+    /// drop back to idle_fps once nothing has changed for settle. Saves CPU time when the
+    /// universe is static.
+    ///
+    /// Some fixtures expect a steady frame rate and flicker or fall back to their own programs
+    /// at a low rate, so this is opt-in. Both rates have to be between 1 and 44.
+    Adaptive {
+        idle_fps: u32,
+        active_fps: u32,
+        settle: Duration,
+    },
+}
+
+impl RefreshMode {
+    /// Fails if one of the adaptive rates is out of range.
+    pub(crate) fn validate(&self) -> Result<(), DmxError> {
+        if let RefreshMode::Adaptive {
+            idle_fps,
+            active_fps,
+            ..
+        } = self
+        {
+            UpdateFrequency::new(idle_fps.saturating_mul(1000))?;
+            UpdateFrequency::new(active_fps.saturating_mul(1000))?;
+        }
+        Ok(())
+    }
+
+    /// The interval until the next frame, given the steady interval and the time since the
+    /// buffer changed the last time.
+    pub(crate) fn frame_interval(&self, steady: Duration, since_change: Duration) -> Duration {
+        match self {
+            RefreshMode::Steady => steady,
+            RefreshMode::Adaptive {
+                idle_fps,
+                active_fps,
+                settle,
+            } => {
+                let fps = if since_change < *settle {
+                    active_fps
+                } else {
+                    idle_fps
+                };
+                Duration::from_micros(1_000_000 / (*fps).max(1) as u64)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_mode_test() {
+        let steady = Duration::from_millis(25);
+        assert_eq!(
+            RefreshMode::Steady.frame_interval(steady, Duration::ZERO),
+            steady
+        );
+
+        let subject = RefreshMode::Adaptive {
+            idle_fps: 2,
+            active_fps: 40,
+            settle: Duration::from_secs(1),
+        };
+        assert_eq!(subject.validate(), Ok(()));
+        assert_eq!(
+            subject.frame_interval(steady, Duration::from_millis(999)),
+            Duration::from_millis(25)
+        );
+        assert_eq!(
+            subject.frame_interval(steady, Duration::from_secs(1)),
+            Duration::from_millis(500)
+        );
+
+        let invalid = RefreshMode::Adaptive {
+            idle_fps: 0,
+            active_fps: 40,
+            settle: Duration::from_secs(1),
+        };
+        assert_eq!(invalid.validate(), Err(DmxError::InvalidUpdateFrequency(0)));
+    }
+}
@@ -0,0 +1,13 @@
+use crate::DMX_UNIVERSE_SIZE;
+
+/// The values OpenDMX::reset_buffer() and the blackout frame on drop set the channels to.
+/// Some fixtures need a specific state to be safe, e.g. an open shutter or a parked position,
+/// instead of all channels at zero.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Set all channels to zero.
+    #[default]
+    Zero,
+    /// Set the channels to the given state, where state[0] is channel 1.
+    Idle(Box<[u8; DMX_UNIVERSE_SIZE]>),
+}
@@ -0,0 +1,137 @@
+use crate::{
+    command::NoResponses, OpenDMX, OpenDmxBuilder, OpenDmxProtocol, SharedSnapshot,
+    DMX_UNIVERSE_SIZE,
+};
+use std::{
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// A universe, that is shared between the application and the background thread started by
+/// OpenDMX::run_shared(). frame[0] is channel 1.
+pub type SharedUniverse = Arc<Mutex<[u8; DMX_UNIVERSE_SIZE]>>;
+
+/// Commands for the background thread started by OpenDMX::run_shared(). Channel values are
+/// written to the shared universe instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlMsg {
+    /// Stop the thread. This will free the device as well.
+    Stop,
+    /// Reset the device.
+    Reset,
+    /// Purge the receive and transmit queues of the device.
+    Purge,
+    /// Set the start code of the following frames.
+    StartCode(u8),
+}
+
+impl From<ControlMsg> for OpenDmxProtocol {
+    fn from(msg: ControlMsg) -> Self {
+        match msg {
+            ControlMsg::Stop => OpenDmxProtocol::Stop,
+            ControlMsg::Reset => OpenDmxProtocol::Reset,
+            ControlMsg::Purge => OpenDmxProtocol::Purge,
+            ControlMsg::StartCode(start_code) => OpenDmxProtocol::StartCode(start_code),
+        }
+    }
+}
+
+impl OpenDMX {
+    /// Same as run() but the channel values are shared with the application instead of being
+    /// send as commands. The application writes directly into the shared universe and the thread
+    /// copies it under the lock once per frame. The channel only carries control messages.
+    ///
+    /// Locking the universe costs about as much as sending a single command, but does not depend
+    /// on the number of changes. Prefer this for renderers, that update the whole universe with
+    /// every tick. Keep the lock short, the thread waits for it before every frame. With only a
+    /// few changes per frame run() is cheaper and keeps commands in order.
+    ///
+    /// There is no response channel, errors are logged and end the thread.
+    pub fn run_shared(id: i32) -> (SharedUniverse, Sender<ControlMsg>, JoinHandle<()>) {
+        let builder = OpenDmxBuilder::new(id);
+        let universe: SharedUniverse = Arc::new(Mutex::new([0; DMX_UNIVERSE_SIZE]));
+        let universe2 = universe.clone();
+        let (sender, receiver) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            builder.pin_current_thread();
+
+            let mut device = match builder.build() {
+                Ok(device) => device,
+                Err(e) => return Self::report_error(&NoResponses, e),
+            };
+            device.shared_universe = Some(universe2);
+            Self::worker(device, receiver, NoResponses, SharedSnapshot::default());
+        });
+
+        (universe, sender, handle)
+    }
+
+    /// Same as run_shared() but uses this already opened device.
+    pub fn spawn_shared(mut self) -> (SharedUniverse, Sender<ControlMsg>, JoinHandle<()>) {
+        let universe: SharedUniverse = Arc::new(Mutex::new([0; DMX_UNIVERSE_SIZE]));
+        self.shared_universe = Some(universe.clone());
+        let (sender, receiver) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            Self::worker(self, receiver, NoResponses, SharedSnapshot::default());
+        });
+
+        (universe, sender, handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MockBackend, OpenDmxBuilder};
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    #[test]
+    fn mock_shared_test() {
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        let (universe, sender, handle) = device.spawn_shared();
+
+        universe.lock().unwrap()[DMX_UNIVERSE_SIZE - 1] = 42;
+        sender.send(ControlMsg::StartCode(0x17)).unwrap();
+
+        let transmitted = |backend: &MockBackend| {
+            backend
+                .writes()
+                .iter()
+                .any(|frame| frame[0] == 0x17 && frame[DMX_UNIVERSE_SIZE] == 42)
+        };
+
+        let start = Instant::now();
+        while !transmitted(&backend) && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        sender.send(ControlMsg::Stop).unwrap();
+        handle.join().unwrap();
+        assert!(transmitted(&backend));
+    }
+
+    #[test]
+    fn mock_shared_error_test() {
+        let backend = MockBackend::new();
+        let device = OpenDmxBuilder::new(0)
+            .settle_delay(Duration::ZERO)
+            .build_with_backend(Box::new(backend.clone()))
+            .unwrap();
+        backend.set_unplugged(true);
+
+        // The failed reset ends the thread instead of panicking.
+        let (_universe, _sender, handle) = device.spawn_shared();
+        handle.join().unwrap();
+        assert!(backend.writes().is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{ChannelMask, DmxError, DMX_SLOT_COUNT};
+use std::sync::{Arc, RwLock};
+
+/// A snapshot of the universe, that is shared between the background thread started by
+/// OpenDMX::run() and the application.
+pub type SharedSnapshot = Arc<RwLock<UniverseSnapshot>>;
+
+/// How UniverseSnapshot::merge() combines two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotMerge<'a> {
+    /// The higher value wins.
+    Htp,
+    /// The values of the other snapshot win.
+    Ltp,
+    /// The values of the other snapshot win on the given channels, the others are kept.
+    Masked(&'a ChannelMask),
+}
+
+/// The values that have been transmitted with the latest frame. The background thread updates
+/// the snapshot after every frame, so the application can observe the device state without
+/// sending requests through the command channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniverseSnapshot {
+    buffer: [u8; DMX_SLOT_COUNT],
+    frames: u64,
+}
+
+impl Default for UniverseSnapshot {
+    fn default() -> Self {
+        UniverseSnapshot {
+            buffer: [0; DMX_SLOT_COUNT],
+            frames: 0,
+        }
+    }
+}
+
+impl UniverseSnapshot {
+    /// Read the value for the given channel.
+    pub fn get_dmx_value(&self, channel: usize) -> Result<u8, DmxError> {
+        if channel >= DMX_SLOT_COUNT {
+            return Err(DmxError::InvalidChannel(channel));
+        }
+        Ok(self.buffer[channel])
+    }
+
+    /// All values of the frame including the start code at index zero.
+    pub fn values(&self) -> &[u8; DMX_SLOT_COUNT] {
+        &self.buffer
+    }
+
+    /// Number of frames that have been transmitted since the device was started.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// The (channel, value) pairs, that differ from the given previous snapshot. Apply them with
+    /// OpenDMX::apply_delta() to transmit only the changes.
+    pub fn diff(&self, previous: &UniverseSnapshot) -> Vec<(usize, u8)> {
+        self.buffer
+            .iter()
+            .zip(previous.buffer.iter())
+            .enumerate()
+            .filter(|(_, (current, previous))| current != previous)
+            .map(|(channel, (current, _))| (channel, *current))
+            .collect()
+    }
+
+    /// Merge the channels of the other snapshot into this one, e.g. to lay overrides over a base
+    /// look. The start code and the frame count are kept. Transmit the result with
+    /// OpenDMX::apply_snapshot().
+    pub fn merge(&mut self, other: &UniverseSnapshot, mode: SnapshotMerge) {
+        for (channel, (value, other)) in self
+            .buffer
+            .iter_mut()
+            .zip(other.buffer.iter())
+            .enumerate()
+            .skip(1)
+        {
+            *value = match mode {
+                SnapshotMerge::Htp => (*value).max(*other),
+                SnapshotMerge::Ltp => *other,
+                SnapshotMerge::Masked(mask) if mask.contains(channel) => *other,
+                SnapshotMerge::Masked(_) => *value,
+            };
+        }
+    }
+
+    /// Record a transmitted frame.
+    pub(crate) fn update(&mut self, buffer: &[u8; DMX_SLOT_COUNT]) {
+        self.buffer = *buffer;
+        self.frames += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_test() {
+        let mut subject = UniverseSnapshot::default();
+        assert_eq!(subject.frames(), 0);
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 0);
+
+        let mut buffer = [0; DMX_SLOT_COUNT];
+        buffer[1] = 255;
+        subject.update(&buffer);
+
+        assert_eq!(subject.frames(), 1);
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 255);
+        assert_eq!(
+            subject.get_dmx_value(DMX_SLOT_COUNT),
+            Err(DmxError::InvalidChannel(DMX_SLOT_COUNT))
+        );
+    }
+
+    #[test]
+    fn merge_test() {
+        let mut base = [0; DMX_SLOT_COUNT];
+        base[1] = 100;
+        base[2] = 100;
+        let mut subject = UniverseSnapshot::default();
+        subject.update(&base);
+
+        let mut overrides = [0; DMX_SLOT_COUNT];
+        overrides[0] = 0x17;
+        overrides[1] = 50;
+        overrides[2] = 200;
+        let mut other = UniverseSnapshot::default();
+        other.update(&overrides);
+
+        let mut htp = subject.clone();
+        htp.merge(&other, SnapshotMerge::Htp);
+        assert_eq!(&htp.values()[..3], &[0, 100, 200]);
+
+        let mut ltp = subject.clone();
+        ltp.merge(&other, SnapshotMerge::Ltp);
+        assert_eq!(&ltp.values()[..3], &[0, 50, 200]);
+
+        let mut mask = ChannelMask::empty();
+        mask.insert(1).unwrap();
+        subject.merge(&other, SnapshotMerge::Masked(&mask));
+        assert_eq!(&subject.values()[..3], &[0, 50, 100]);
+        assert_eq!(subject.frames(), 1);
+    }
+
+    #[test]
+    fn diff_test() {
+        let previous = UniverseSnapshot::default();
+        let mut subject = UniverseSnapshot::default();
+        assert!(subject.diff(&previous).is_empty());
+
+        let mut buffer = [0; DMX_SLOT_COUNT];
+        buffer[1] = 255;
+        buffer[512] = 1;
+        subject.update(&buffer);
+
+        assert_eq!(subject.diff(&previous), vec![(1, 255), (512, 1)]);
+    }
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+/// Counters of the background thread started by run() (see OpenDmxProtocol::GetStats).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Number of frames, that have been transmitted.
+    pub frames: u64,
+    /// Number of frames, that could not be transmitted.
+    pub failed_frames: u64,
+    /// Time since the thread started transmitting.
+    pub running: Duration,
+}
+
+impl FrameStats {
+    /// Average number of transmitted frames per second. Zero before the first frame.
+    pub fn frames_per_second(&self) -> f64 {
+        if self.running.is_zero() {
+            return 0.0;
+        }
+        self.frames as f64 / self.running.as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_stats_test() {
+        assert_eq!(FrameStats::default().frames_per_second(), 0.0);
+
+        let subject = FrameStats {
+            frames: 80,
+            failed_frames: 0,
+            running: Duration::from_secs(2),
+        };
+        assert_eq!(subject.frames_per_second(), 40.0);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::DMX_UNIVERSE_SIZE;
+use std::time::Duration;
+
+/// Patterns transmitted by the run() thread to verify the addressing of the fixtures (see
+/// OpenDmxProtocol::TestPattern).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestPattern {
+    /// Set one channel after another to full, starting with channel 1. Each channel is held for
+    /// dwell, the pattern starts over after channel 512.
+    Walk { dwell: Duration },
+    /// Set all channels to full.
+    AllOn,
+    /// Set all odd channels to full and all even channels to zero.
+    Checkerboard,
+}
+
+impl TestPattern {
+    /// Values of all channels after the pattern has been running for elapsed.
+    pub(crate) fn frame(&self, elapsed: Duration) -> [u8; DMX_UNIVERSE_SIZE] {
+        let mut frame = [0; DMX_UNIVERSE_SIZE];
+        match self {
+            TestPattern::Walk { dwell } => {
+                let index = if dwell.is_zero() {
+                    0
+                } else {
+                    (elapsed.as_nanos() / dwell.as_nanos()) as usize % DMX_UNIVERSE_SIZE
+                };
+                frame[index] = 255;
+            }
+            TestPattern::AllOn => frame = [255; DMX_UNIVERSE_SIZE],
+            TestPattern::Checkerboard => {
+                for value in frame.iter_mut().step_by(2) {
+                    *value = 255;
+                }
+            }
+        }
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_test() {
+        let walk = TestPattern::Walk {
+            dwell: Duration::from_millis(100),
+        };
+        assert_eq!(walk.frame(Duration::ZERO)[0], 255);
+        assert_eq!(
+            walk.frame(Duration::ZERO)
+                .iter()
+                .filter(|v| **v > 0)
+                .count(),
+            1
+        );
+        assert_eq!(walk.frame(Duration::from_millis(250))[2], 255);
+        // Starts over after the last channel.
+        assert_eq!(walk.frame(Duration::from_millis(51450))[2], 255);
+
+        let walk = TestPattern::Walk {
+            dwell: Duration::ZERO,
+        };
+        assert_eq!(walk.frame(Duration::from_secs(1))[0], 255);
+
+        assert_eq!(
+            TestPattern::AllOn.frame(Duration::ZERO),
+            [255; DMX_UNIVERSE_SIZE]
+        );
+
+        let checkerboard = TestPattern::Checkerboard.frame(Duration::ZERO);
+        assert_eq!(checkerboard[0], 255);
+        assert_eq!(checkerboard[1], 0);
+        assert_eq!(checkerboard[511], 0);
+    }
+}
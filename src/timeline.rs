@@ -0,0 +1,256 @@
+use crate::{virtual_dmx::frame_line, DmxError, FrameCallback, Universe, DMX_UNIVERSE_SIZE};
+use std::{
+    io::{BufRead, Write},
+    time::{Duration, Instant},
+};
+
+/// First line of a timeline, followed by the samples.
+const HEADER: &str = "open_dmx timeline 1";
+
+/// Records the frames of a device as a timeline, that keeps long recordings small (see
+/// Timeline). Unlike VirtualDmx, which writes every frame, a frame is only written if its
+/// channels have changed or the interval has elapsed since the latest sample. The frame in front
+/// of a change is written as well, so a replay holds the values until the change instead of
+/// fading into it.
+///
+/// The timeline is text. The first line is `open_dmx timeline 1`, every further line is one
+/// sample: the microseconds since the recording started and the channels packed with
+/// Universe::pack() as hex, e.g. `100000 ff00ff000200` for a blackout.
+#[derive(Debug)]
+pub struct TimelineRecorder<W: Write> {
+    writer: W,
+    interval: Duration,
+    /// Time and channels of the latest sample.
+    written: Option<(Duration, [u8; DMX_UNIVERSE_SIZE])>,
+    /// Time and channels of the latest frame, if it has not been written.
+    pending: Option<(Duration, [u8; DMX_UNIVERSE_SIZE])>,
+}
+
+impl<W: Write> TimelineRecorder<W> {
+    /// Start a timeline, that samples the frames at least every interval.
+    pub fn new(mut writer: W, interval: Duration) -> Result<Self, DmxError> {
+        match writeln!(writer, "{}", HEADER) {
+            Ok(_) => {}
+            Err(_) => return Err(DmxError::Io("write timeline")),
+        }
+
+        Ok(TimelineRecorder {
+            writer,
+            interval,
+            written: None,
+            pending: None,
+        })
+    }
+
+    /// Record the given frame, where frame[0] is channel 1, transmitted elapsed after the
+    /// recording started. Returns whether a sample has been written.
+    pub fn record(
+        &mut self,
+        elapsed: Duration,
+        frame: &[u8; DMX_UNIVERSE_SIZE],
+    ) -> Result<bool, DmxError> {
+        let due = match &self.written {
+            None => true,
+            Some((_, channels)) if channels != frame => {
+                // Hold the previous values until the change.
+                if let Some((at, channels)) = self.pending.take() {
+                    self.write_sample(at, &channels)?;
+                }
+                true
+            }
+            Some((at, _)) => elapsed.saturating_sub(*at) >= self.interval,
+        };
+
+        if !due {
+            self.pending = Some((elapsed, *frame));
+            return Ok(false);
+        }
+        self.write_sample(elapsed, frame)?;
+        Ok(true)
+    }
+
+    /// Write the frame, that has not been sampled yet, and return the writer.
+    pub fn finish(mut self) -> Result<W, DmxError> {
+        if let Some((at, channels)) = self.pending.take() {
+            self.write_sample(at, &channels)?;
+        }
+        match self.writer.flush() {
+            Ok(_) => Ok(self.writer),
+            Err(_) => Err(DmxError::Io("write timeline")),
+        }
+    }
+
+    fn write_sample(
+        &mut self,
+        elapsed: Duration,
+        frame: &[u8; DMX_UNIVERSE_SIZE],
+    ) -> Result<(), DmxError> {
+        let line = frame_line(elapsed, &Universe::from(*frame).pack());
+
+        match self.writer.write_all(line.as_bytes()) {
+            Ok(_) => {}
+            Err(_) => return Err(DmxError::Io("write timeline")),
+        }
+        self.written = Some((elapsed, *frame));
+        self.pending = None;
+        Ok(())
+    }
+}
+
+impl<W: Write + Send + 'static> TimelineRecorder<W> {
+    /// Record every transmitted frame of the run() thread (see OpenDMX::set_on_frame()). The
+    /// recording starts now. Write errors are logged.
+    pub fn into_callback(mut self) -> FrameCallback {
+        let started = Instant::now();
+        Box::new(move |frame| match self.record(started.elapsed(), frame) {
+            Ok(_) => {}
+            Err(e) => log::warn!("Could not record frame. Error: {}", e),
+        })
+    }
+}
+
+/// A timeline written by TimelineRecorder.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    samples: Vec<(Duration, Universe)>,
+}
+
+impl Timeline {
+    /// Read a timeline written by TimelineRecorder.
+    pub fn read<R: BufRead>(reader: R) -> Result<Self, DmxError> {
+        let mut lines = reader.lines();
+        match lines.next() {
+            Some(Ok(header)) if header == HEADER => {}
+            Some(Err(_)) => return Err(DmxError::Io("read timeline")),
+            _ => return Err(DmxError::Decode("timeline header")),
+        }
+
+        let mut samples: Vec<(Duration, Universe)> = Vec::new();
+        for line in lines {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => return Err(DmxError::Io("read timeline")),
+            };
+            let sample = Self::parse_sample(&line)?;
+            if samples.last().is_some_and(|(at, _)| *at > sample.0) {
+                return Err(DmxError::Decode("timeline out of order"));
+            }
+            samples.push(sample);
+        }
+
+        Ok(Timeline { samples })
+    }
+
+    fn parse_sample(line: &str) -> Result<(Duration, Universe), DmxError> {
+        let invalid = DmxError::Decode("timeline sample");
+        let (micros, hex) = line.split_once(' ').ok_or(invalid)?;
+        let micros: u64 = micros.parse().map_err(|_| invalid)?;
+
+        if !hex.len().is_multiple_of(2) {
+            return Err(invalid);
+        }
+        let mut packed = Vec::with_capacity(hex.len() / 2);
+        for pair in hex.as_bytes().chunks_exact(2) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid)?;
+            packed.push(u8::from_str_radix(pair, 16).map_err(|_| invalid)?);
+        }
+
+        Ok((Duration::from_micros(micros), Universe::unpack(&packed)?))
+    }
+
+    /// The samples, the earliest first.
+    pub fn samples(&self) -> &[(Duration, Universe)] {
+        &self.samples
+    }
+
+    /// Time of the latest sample.
+    pub fn duration(&self) -> Duration {
+        self.samples.last().map_or(Duration::ZERO, |(at, _)| *at)
+    }
+
+    /// The channels at the given time since the recording started, interpolated between the
+    /// samples around it. Before the first and after the latest sample, the values of that
+    /// sample are returned. None for an empty timeline.
+    pub fn replay(&self, at: Duration) -> Option<Universe> {
+        let next = self.samples.partition_point(|(time, _)| *time <= at);
+        if next == 0 {
+            return self.samples.first().map(|(_, universe)| universe.clone());
+        }
+        let (from_time, from) = &self.samples[next - 1];
+        let Some((to_time, to)) = self.samples.get(next) else {
+            return Some(from.clone());
+        };
+
+        let progress = (at - *from_time).as_secs_f64() / (*to_time - *from_time).as_secs_f64();
+        let (from, to) = (from.snapshot(), to.snapshot());
+        let mut frame = [0; DMX_UNIVERSE_SIZE];
+        for (value, (a, b)) in frame.iter_mut().zip(from.iter().zip(to.iter())) {
+            *value = (*a as f64 + (*b as f64 - *a as f64) * progress).round() as u8;
+        }
+        Some(Universe::from(frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeline_test() {
+        let mut subject = TimelineRecorder::new(Vec::new(), Duration::from_millis(100)).unwrap();
+
+        // 40 frames per second, channel 1 goes to full after 300ms.
+        let mut written = Vec::new();
+        for index in 0..=20 {
+            let mut frame = [0; DMX_UNIVERSE_SIZE];
+            if index >= 12 {
+                frame[0] = 255;
+            }
+            if subject
+                .record(Duration::from_millis(index * 25), &frame)
+                .unwrap()
+            {
+                written.push(index * 25);
+            }
+        }
+        assert_eq!(written, [0, 100, 200, 300, 400, 500]);
+
+        let data = subject.finish().unwrap();
+        let text = String::from_utf8(data.clone()).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some(HEADER));
+        assert_eq!(lines.next(), Some("0 ff00ff000200"));
+
+        let timeline = Timeline::read(&data[..]).unwrap();
+        let times: Vec<u128> = timeline
+            .samples()
+            .iter()
+            .map(|(at, _)| at.as_millis())
+            .collect();
+        // The frame in front of the change has been written, too.
+        assert_eq!(times, [0, 100, 200, 275, 300, 400, 500]);
+        assert_eq!(timeline.duration(), Duration::from_millis(500));
+
+        let channel_1 = |micros: u64| {
+            timeline
+                .replay(Duration::from_micros(micros))
+                .unwrap()
+                .get(1)
+                .unwrap()
+        };
+        assert_eq!(channel_1(250_000), 0);
+        assert_eq!(channel_1(287_500), 128);
+        assert_eq!(channel_1(350_000), 255);
+        assert_eq!(channel_1(10_000_000), 255);
+
+        assert_eq!(Timeline::default().replay(Duration::ZERO), None);
+        assert_eq!(
+            Timeline::read(&b"garbage\n"[..]),
+            Err(DmxError::Decode("timeline header"))
+        );
+        assert_eq!(
+            Timeline::read(format!("{}\n10 ff\n", HEADER).as_bytes()),
+            Err(DmxError::Decode("truncated run"))
+        );
+    }
+}
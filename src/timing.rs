@@ -0,0 +1,113 @@
+use crate::{
+    DmxError, UpdateFrequency, DEFAULT_LATENCY_TIMER, DEFAULT_SETTLE_DELAY, DMX_BREAK, DMX_MAB,
+    DMX_MIN_BREAK, DMX_MIN_MAB, DMX_SLOT_COUNT,
+};
+use std::time::Duration;
+
+/// Time to transmit a single slot in microseconds: a start bit, 8 data bits and 2 stop bits
+/// at 250 kbaud.
+const SLOT_TIME: u64 = 44;
+
+/// All timing settings of a device in one place, so they can be reproduced and shared between
+/// installations (see OpenDmxBuilder::timing()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timing {
+    /// Length of the break in front of every frame.
+    pub break_time: Duration,
+    /// Length of the mark after break.
+    pub mark_after_break: Duration,
+    /// Time the line is held idle after a frame (see OpenDmxBuilder::mark_before_break()).
+    pub mark_before_break: Duration,
+    /// See OpenDmxBuilder::settle_delay().
+    pub settle_delay: Duration,
+    /// See OpenDmxBuilder::latency_timer().
+    pub latency_timer: Duration,
+    pub update_frequency: UpdateFrequency,
+}
+
+impl Timing {
+    /// Comfortable margins, that work with most fixtures.
+    pub fn dmx_standard() -> Self {
+        Timing {
+            break_time: Duration::from_micros(DMX_BREAK),
+            mark_after_break: Duration::from_micros(DMX_MAB),
+            mark_before_break: Duration::ZERO,
+            settle_delay: DEFAULT_SETTLE_DELAY,
+            latency_timer: DEFAULT_LATENCY_TIMER,
+            update_frequency: UpdateFrequency::DEFAULT,
+        }
+    }
+
+    /// The shortest break and mark after break commonly accepted and the highest update
+    /// frequency. Only use this with devices, that are already initialized.
+    pub fn fast() -> Self {
+        Timing {
+            break_time: Duration::from_micros(92),
+            mark_after_break: Duration::from_micros(12),
+            mark_before_break: Duration::ZERO,
+            settle_delay: Duration::from_millis(100),
+            latency_timer: Duration::from_millis(1),
+            update_frequency: UpdateFrequency::MAX,
+        }
+    }
+
+    /// Check the settings against the dmx standard: the break has to be at least 88µs, the mark
+    /// after break at least 8µs, and a full frame has to fit into the interval given by the
+    /// update frequency.
+    pub fn validate(&self) -> Result<(), DmxError> {
+        if self.break_time < Duration::from_micros(DMX_MIN_BREAK) {
+            return Err(DmxError::InvalidTiming("break"));
+        }
+        if self.mark_after_break < Duration::from_micros(DMX_MIN_MAB) {
+            return Err(DmxError::InvalidTiming("mark after break"));
+        }
+
+        let frequency = UpdateFrequency::new(self.update_frequency.get())?;
+        let interval = Duration::from_micros(1_000_000_000 / frequency.get() as u64);
+        let frame = self.break_time
+            + self.mark_after_break
+            + Duration::from_micros(SLOT_TIME * DMX_SLOT_COUNT as u64)
+            + self.mark_before_break;
+        if frame > interval {
+            return Err(DmxError::InvalidTiming("frame length"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self::dmx_standard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timing_test() {
+        assert_eq!(Timing::dmx_standard().validate(), Ok(()));
+        assert_eq!(Timing::fast().validate(), Ok(()));
+
+        let mut subject = Timing::dmx_standard();
+        subject.break_time = Duration::from_micros(87);
+        assert_eq!(subject.validate(), Err(DmxError::InvalidTiming("break")));
+
+        let mut subject = Timing::dmx_standard();
+        subject.mark_after_break = Duration::from_micros(7);
+        assert_eq!(
+            subject.validate(),
+            Err(DmxError::InvalidTiming("mark after break"))
+        );
+
+        let mut subject = Timing::fast();
+        subject.mark_before_break = Duration::from_millis(1);
+        assert_eq!(
+            subject.validate(),
+            Err(DmxError::InvalidTiming("frame length"))
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{DmxError, OpenDMX};
+
+/// Collects channel updates and applies them all at once (see OpenDMX::transaction()).
+/// Nothing is written into the buffer unless all channels are valid.
+pub struct ChannelTransaction<'a> {
+    device: &'a mut OpenDMX,
+    values: Vec<(usize, u8)>,
+}
+
+impl<'a> ChannelTransaction<'a> {
+    pub(crate) fn new(device: &'a mut OpenDMX) -> Self {
+        ChannelTransaction {
+            device,
+            values: Vec::new(),
+        }
+    }
+
+    /// Queue a new value for the given channel. Later values for the same channel overwrite
+    /// earlier ones.
+    pub fn set(&mut self, channel: usize, value: u8) -> &mut Self {
+        self.values.push((channel, value));
+        self
+    }
+
+    /// Validate all queued values and write them into the buffer. Returns the first invalid
+    /// channel, in which case the buffer remains untouched.
+    pub fn commit(self) -> Result<(), DmxError> {
+        let len = self.device.buffer.len();
+        if let Some((channel, _)) = self.values.iter().find(|(c, _)| *c >= len) {
+            return Err(DmxError::InvalidChannel(*channel));
+        }
+
+        for (channel, value) in self.values {
+            self.device.buffer[channel] = value;
+        }
+        self.device.dirty = true;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MockBackend, DMX_SLOT_COUNT};
+
+    use super::*;
+
+    #[test]
+    fn mock_transaction_test() {
+        let mut subject = OpenDMX::new_with_backend(MockBackend::new()).unwrap();
+
+        let mut transaction = subject.transaction();
+        transaction.set(1, 10).set(2, 20);
+        transaction.commit().unwrap();
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 10);
+        assert_eq!(subject.get_dmx_value(2).unwrap(), 20);
+
+        // A single invalid channel discards the whole transaction.
+        let mut transaction = subject.transaction();
+        transaction.set(1, 0).set(DMX_SLOT_COUNT, 0);
+        let e = transaction.commit();
+        assert_eq!(e, Err(DmxError::InvalidChannel(DMX_SLOT_COUNT)));
+        assert_eq!(subject.get_dmx_value(1).unwrap(), 10);
+    }
+}
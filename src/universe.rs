@@ -0,0 +1,240 @@
+use crate::{ChannelMask, DmxError, DMX_SLOT_COUNT, DMX_UNIVERSE_SIZE};
+
+/// The values of all channels of a universe, independent of a device. Compose the values in
+/// memory and transmit them with OpenDMX::output(). Channels are addressed from 1 to
+/// DMX_UNIVERSE_SIZE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Universe([u8; DMX_UNIVERSE_SIZE]);
+
+impl Default for Universe {
+    fn default() -> Self {
+        Universe([0; DMX_UNIVERSE_SIZE])
+    }
+}
+
+impl From<[u8; DMX_UNIVERSE_SIZE]> for Universe {
+    fn from(frame: [u8; DMX_UNIVERSE_SIZE]) -> Self {
+        Universe(frame)
+    }
+}
+
+/// Accepts the 512 channels or a whole frame of 513 slots. The start code of a frame is dropped.
+impl TryFrom<&[u8]> for Universe {
+    type Error = DmxError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, DmxError> {
+        let channels = match bytes.len() {
+            DMX_UNIVERSE_SIZE => bytes,
+            DMX_SLOT_COUNT => &bytes[1..],
+            _ => return Err(DmxError::Decode("expected 512 or 513 bytes")),
+        };
+
+        let mut universe = Universe::new();
+        universe.0.copy_from_slice(channels);
+        Ok(universe)
+    }
+}
+
+/// The 512 channels without a start code.
+impl From<Universe> for Vec<u8> {
+    fn from(universe: Universe) -> Self {
+        universe.0.to_vec()
+    }
+}
+
+impl Universe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of the given channel.
+    pub fn set(&mut self, channel: usize, value: u8) -> Result<(), DmxError> {
+        let index = Self::index(channel)?;
+        self.0[index] = value;
+
+        Ok(())
+    }
+
+    /// Get the value of the given channel.
+    pub fn get(&self, channel: usize) -> Result<u8, DmxError> {
+        Ok(self.0[Self::index(channel)?])
+    }
+
+    /// Merge the other universe into this one. The higher value wins (HTP).
+    pub fn merge_htp(&mut self, other: &Universe) {
+        for (value, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *value = (*value).max(*other);
+        }
+    }
+
+    /// Merge the given channels of the other universe into this one. The values of the other
+    /// universe win (LTP).
+    pub fn merge_ltp(&mut self, other: &Universe, channels: &ChannelMask) {
+        for (index, value) in self.0.iter_mut().enumerate() {
+            if channels.contains(index + 1) {
+                *value = other.0[index];
+            }
+        }
+    }
+
+    /// Scale all channels by the given factor. The factor is clamped to 0.0 to 1.0.
+    pub fn scale(&mut self, factor: f32) {
+        let factor = factor.clamp(0.0, 1.0);
+        for value in self.0.iter_mut() {
+            *value = (*value as f32 * factor).round() as u8;
+        }
+    }
+
+    /// A copy of all values, where frame[0] is channel 1.
+    pub fn snapshot(&self) -> [u8; DMX_UNIVERSE_SIZE] {
+        self.0
+    }
+
+    /// Run-length encode all channels for transport over a slow link. Each run of equal values
+    /// is stored as two bytes, the length of the run (1 to 255) followed by the value. A
+    /// blackout packs into 6 bytes, a universe without any runs into 1024 bytes.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut packed = Vec::new();
+        let mut values = self.0.iter().peekable();
+        while let Some(value) = values.next() {
+            let mut length: u8 = 1;
+            while length < u8::MAX && values.peek() == Some(&value) {
+                values.next();
+                length += 1;
+            }
+            packed.push(length);
+            packed.push(*value);
+        }
+        packed
+    }
+
+    /// Decode a universe packed with pack(). Fails if the data does not contain exactly 512
+    /// values.
+    pub fn unpack(packed: &[u8]) -> Result<Universe, DmxError> {
+        if !packed.len().is_multiple_of(2) {
+            return Err(DmxError::Decode("truncated run"));
+        }
+
+        let mut universe = Universe::new();
+        let mut index = 0;
+        for run in packed.chunks_exact(2) {
+            let (length, value) = (run[0] as usize, run[1]);
+            if length == 0 {
+                return Err(DmxError::Decode("empty run"));
+            }
+            if index + length > DMX_UNIVERSE_SIZE {
+                return Err(DmxError::Decode("too many channels"));
+            }
+            universe.0[index..index + length].fill(value);
+            index += length;
+        }
+
+        if index != DMX_UNIVERSE_SIZE {
+            return Err(DmxError::Decode("too few channels"));
+        }
+        Ok(universe)
+    }
+
+    fn index(channel: usize) -> Result<usize, DmxError> {
+        if channel == 0 || channel > DMX_UNIVERSE_SIZE {
+            return Err(DmxError::InvalidChannel(channel));
+        }
+        Ok(channel - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn universe_test() {
+        let mut subject = Universe::new();
+        subject.set(1, 100).unwrap();
+        subject.set(DMX_UNIVERSE_SIZE, 200).unwrap();
+        assert_eq!(subject.set(0, 1), Err(DmxError::InvalidChannel(0)));
+        assert_eq!(
+            subject.get(DMX_UNIVERSE_SIZE + 1),
+            Err(DmxError::InvalidChannel(DMX_UNIVERSE_SIZE + 1))
+        );
+
+        let mut other = Universe::new();
+        other.set(1, 50).unwrap();
+        other.set(2, 60).unwrap();
+
+        subject.merge_htp(&other);
+        assert_eq!(subject.get(1), Ok(100));
+        assert_eq!(subject.get(2), Ok(60));
+
+        let mut channels = ChannelMask::empty();
+        channels.insert(1).unwrap();
+        subject.merge_ltp(&other, &channels);
+        assert_eq!(subject.get(1), Ok(50));
+
+        subject.scale(0.5);
+        assert_eq!(subject.get(1), Ok(25));
+        assert_eq!(subject.snapshot()[DMX_UNIVERSE_SIZE - 1], 100);
+    }
+
+    #[test]
+    fn bytes_test() {
+        let mut frame = vec![0xCC; DMX_SLOT_COUNT];
+        frame[1] = 10;
+        let subject = Universe::try_from(&frame[..]).unwrap();
+        assert_eq!(subject.get(1), Ok(10));
+        assert_eq!(subject.get(2), Ok(0xCC));
+        assert_eq!(Universe::try_from(&frame[1..]), Ok(subject.clone()));
+
+        let bytes: Vec<u8> = subject.into();
+        assert_eq!(bytes.len(), DMX_UNIVERSE_SIZE);
+        assert_eq!(bytes[0], 10);
+
+        for len in [0, DMX_UNIVERSE_SIZE - 1, DMX_SLOT_COUNT + 1] {
+            assert_eq!(
+                Universe::try_from(&vec![0; len][..]),
+                Err(DmxError::Decode("expected 512 or 513 bytes"))
+            );
+        }
+    }
+
+    #[test]
+    fn pack_test() {
+        let blackout = Universe::new();
+        assert_eq!(blackout.pack(), vec![255, 0, 255, 0, 2, 0]);
+
+        let mut sparse = Universe::new();
+        sparse.set(1, 255).unwrap();
+        sparse.set(100, 10).unwrap();
+        sparse.set(101, 10).unwrap();
+        sparse.set(DMX_UNIVERSE_SIZE, 1).unwrap();
+
+        // A simple linear congruential generator, so the test is reproducible.
+        let mut seed: u32 = 1;
+        let mut random = Universe::new();
+        for channel in 1..=DMX_UNIVERSE_SIZE {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            random.set(channel, (seed >> 16) as u8).unwrap();
+        }
+
+        for universe in [blackout, sparse, random] {
+            assert_eq!(Universe::unpack(&universe.pack()), Ok(universe));
+        }
+
+        assert_eq!(
+            Universe::unpack(&[255, 0, 255]),
+            Err(DmxError::Decode("truncated run"))
+        );
+        assert_eq!(
+            Universe::unpack(&[0, 0]),
+            Err(DmxError::Decode("empty run"))
+        );
+        assert_eq!(
+            Universe::unpack(&[255, 0, 255, 0, 3, 0]),
+            Err(DmxError::Decode("too many channels"))
+        );
+        assert_eq!(
+            Universe::unpack(&[255, 0]),
+            Err(DmxError::Decode("too few channels"))
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{DmxBackend, DmxError, OpenDMX};
+use libftd2xx::{
+    BitsPerWord, DeviceInfo, DeviceStatus, FtStatus, Parity, StopBits, TimeoutError, Version,
+};
+use std::{
+    fmt::Write as _,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// A backend, that writes the frames to a file or named pipe instead of a device. Every frame is
+/// written as one line, the microseconds since the device has been opened followed by the start
+/// code and the channels as hex, e.g. `25013 00ff8000...`. Breaks are not recorded.
+#[derive(Debug)]
+pub struct VirtualDmx {
+    file: File,
+    opened: Instant,
+    latency_timer: Duration,
+}
+
+impl VirtualDmx {
+    /// Create or truncate the file at the given path. Named pipes are opened for writing.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, DmxError> {
+        let file = match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+        {
+            Ok(f) => f,
+            Err(_) => return Err(DmxError::Io("open virtual device")),
+        };
+
+        Ok(VirtualDmx {
+            file,
+            opened: Instant::now(),
+            // The ftdi default.
+            latency_timer: Duration::from_millis(16),
+        })
+    }
+
+    fn write_frame(&mut self, buf: &[u8]) -> Result<(), FtStatus> {
+        let line = frame_line(self.opened.elapsed(), buf);
+        match self.file.write_all(line.as_bytes()) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(FtStatus::IO_ERROR),
+        }
+    }
+}
+
+/// One line of the capture format: the elapsed microseconds, a space and the
+/// frame in hex.
+pub(crate) fn frame_line(elapsed: Duration, buf: &[u8]) -> String {
+    let micros = elapsed.as_micros();
+    let mut line = String::with_capacity(20 + buf.len() * 2);
+    // Writing into a String can't fail.
+    let _ = write!(line, "{} ", micros);
+    for value in buf {
+        let _ = write!(line, "{:02x}", value);
+    }
+    line.push('\n');
+    line
+}
+
+impl DmxBackend for VirtualDmx {
+    fn device_info(&mut self) -> Result<DeviceInfo, FtStatus> {
+        Ok(DeviceInfo {
+            description: "Virtual".to_owned(),
+            ..Default::default()
+        })
+    }
+
+    fn driver_version(&mut self) -> Result<Version, FtStatus> {
+        Err(FtStatus::NOT_SUPPORTED)
+    }
+
+    fn reset(&mut self) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_baud_rate(&mut self, _baud_rate: u32) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_data_characteristics(
+        &mut self,
+        _bits_per_word: BitsPerWord,
+        _stop_bits: StopBits,
+        _parity: Parity,
+    ) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_timeouts(
+        &mut self,
+        _read_time_out: Duration,
+        _write_time_out: Duration,
+    ) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_latency_timer(&mut self, timer: Duration) -> Result<(), FtStatus> {
+        self.latency_timer = timer;
+        Ok(())
+    }
+
+    fn latency_timer(&mut self) -> Result<Duration, FtStatus> {
+        Ok(self.latency_timer)
+    }
+
+    fn set_usb_parameters(&mut self, _transfer_size: u32) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_flow_control_none(&mut self) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_flow_control_rts_cts(&mut self) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_flow_control_dtr_dsr(&mut self) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_flow_control_xon_xoff(&mut self, _xon: u8, _xoff: u8) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_rts(&mut self) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn clear_rts(&mut self) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn purge_rx(&mut self) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn purge_tx(&mut self) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn set_break(&mut self, _on: bool) -> Result<(), FtStatus> {
+        Ok(())
+    }
+
+    fn queue_status(&mut self) -> Result<usize, FtStatus> {
+        Ok(0)
+    }
+
+    fn read_all(&mut self, buf: &mut [u8]) -> Result<(), TimeoutError> {
+        // Nothing is ever received.
+        if !buf.is_empty() {
+            return Err(TimeoutError::Timeout {
+                actual: 0,
+                expected: buf.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), TimeoutError> {
+        self.write_frame(buf).map_err(TimeoutError::FtStatus)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, FtStatus> {
+        self.write_frame(buf)?;
+        Ok(buf.len())
+    }
+
+    fn status(&mut self) -> Result<DeviceStatus, FtStatus> {
+        Ok(DeviceStatus {
+            ammount_in_rx_queue: 0,
+            ammount_in_tx_queue: 0,
+            event_status: 0,
+        })
+    }
+
+    fn close(&mut self) -> Result<(), FtStatus> {
+        match self.file.flush() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(FtStatus::IO_ERROR),
+        }
+    }
+}
+
+impl OpenDMX {
+    /// Create a device, that writes its frames to the file or named pipe at the given path
+    /// instead of a dongle (see VirtualDmx). Useful for development and CI machines without
+    /// hardware, e.g. with spawn().
+    pub fn virtual_to<P: AsRef<Path>>(path: P) -> Result<Self, DmxError> {
+        Self::new_with_backend(VirtualDmx::create(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DMX_UNIVERSE_SIZE;
+    use std::fs;
+
+    #[test]
+    fn virtual_dmx_test() {
+        let path = std::env::temp_dir().join(format!("open_dmx_virtual_{}", std::process::id()));
+        let mut subject = OpenDMX::virtual_to(&path).unwrap();
+        subject.reset().unwrap();
+        subject.set_dmx_value(1, 0xFF).unwrap();
+        subject.write().unwrap();
+        subject.close().unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let (timestamp, frame) = lines[0].split_once(' ').unwrap();
+        assert!(timestamp.parse::<u128>().is_ok());
+        assert_eq!(frame.len(), (DMX_UNIVERSE_SIZE + 1) * 2);
+        assert!(frame.starts_with("00ff00"));
+    }
+}